@@ -0,0 +1,40 @@
+#import bevy_sprite::mesh2d_view_bindings
+
+struct SkyMaterial {
+    top_color: vec4<f32>,
+    bottom_color: vec4<f32>,
+    cloud_color: vec4<f32>,
+    scroll: f32,
+};
+
+@group(1) @binding(0)
+var<uniform> material: SkyMaterial;
+
+struct FragmentInput {
+    #import bevy_sprite::mesh2d_vertex_output
+};
+
+fn hash(p: vec2<f32>) -> f32 {
+    return fract(sin(dot(p, vec2<f32>(127.1, 311.7))) * 43758.5453);
+}
+
+// Smooth value noise, enough for soft cloud shapes
+fn noise(p: vec2<f32>) -> f32 {
+    let i = floor(p);
+    let f = fract(p);
+    let u = f * f * (3.0 - 2.0 * f);
+    let bottom = mix(hash(i), hash(i + vec2<f32>(1.0, 0.0)), u.x);
+    let top = mix(hash(i + vec2<f32>(0.0, 1.0)), hash(i + vec2<f32>(1.0, 1.0)), u.x);
+    return mix(bottom, top, u.y);
+}
+
+@fragment
+fn fragment(in: FragmentInput) -> @location(0) vec4<f32> {
+    // uv.y runs from 0 at the top of the quad to 1 at the bottom
+    let sky = mix(material.top_color, material.bottom_color, in.uv.y);
+    let p = vec2<f32>(in.uv.x + material.scroll, in.uv.y) * vec2<f32>(4.0, 6.0);
+    let density = noise(p) * 0.65 + noise(p * 2.0) * 0.35;
+    // Clouds thin out toward the horizon
+    let clouds = smoothstep(0.5, 0.75, density) * (1.0 - in.uv.y);
+    return mix(sky, material.cloud_color, clouds * material.cloud_color.a);
+}
@@ -0,0 +1,176 @@
+use bevy::prelude::*;
+
+use crate::{
+    autopilot::AutopilotControl, counts_for_records, glide::GlideStreak, lang::Language, save,
+    Comeback, DailyChallenge, Flash, GameConfig, GameState, HudRoot, PipeRng, RunTime, Score,
+};
+
+const ACHIEVEMENTS_PATH: &str = "achievements.txt";
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Achievement {
+    Score10,
+    Score50,
+    Survive60,
+    Glide3,
+}
+
+impl Achievement {
+    const ALL: [Achievement; 4] = [
+        Achievement::Score10,
+        Achievement::Score50,
+        Achievement::Survive60,
+        Achievement::Glide3,
+    ];
+
+    // Stored in the save file, so these must never change
+    fn id(self) -> &'static str {
+        match self {
+            Achievement::Score10 => "score_10",
+            Achievement::Score50 => "score_50",
+            Achievement::Survive60 => "survive_60",
+            Achievement::Glide3 => "glide_3",
+        }
+    }
+
+    pub fn title(self, language: &Language) -> String {
+        language
+            .t(&format!("achievement.{}.title", self.id()))
+            .to_string()
+    }
+
+    pub fn description(self, language: &Language) -> String {
+        language
+            .t(&format!("achievement.{}.description", self.id()))
+            .to_string()
+    }
+
+    fn reached(self, score: u32, run_time: f32, glide_streak: u32) -> bool {
+        match self {
+            Achievement::Score10 => score >= 10,
+            Achievement::Score50 => score >= 50,
+            Achievement::Survive60 => run_time >= 60.0,
+            Achievement::Glide3 => glide_streak >= 3,
+        }
+    }
+}
+
+/// Unlocked achievements, stored one id per line. Ids the game doesn't know are dropped.
+#[derive(Resource)]
+pub struct Achievements {
+    unlocked: Vec<Achievement>,
+    // Unlocked since launch, oldest first
+    session: Vec<Achievement>,
+}
+
+impl Achievements {
+    fn load() -> Self {
+        let unlocked = std::fs::read_to_string(ACHIEVEMENTS_PATH)
+            .map(|contents| {
+                Achievement::ALL
+                    .into_iter()
+                    .filter(|achievement| contents.lines().any(|line| line == achievement.id()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self {
+            unlocked,
+            session: Vec::new(),
+        }
+    }
+
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.contains(&achievement)
+    }
+
+    /// Achievements unlocked since the game started, oldest first.
+    pub fn session(&self) -> &[Achievement] {
+        &self.session
+    }
+
+    fn unlock(&mut self, achievement: Achievement) {
+        self.unlocked.push(achievement);
+        self.session.push(achievement);
+        let contents: String = self
+            .unlocked
+            .iter()
+            .map(|achievement| format!("{}\n", achievement.id()))
+            .collect();
+        if let Err(err) = save::write_atomic(ACHIEVEMENTS_PATH, contents) {
+            error!(
+                "Failed to save achievements to {}: {}",
+                ACHIEVEMENTS_PATH, err
+            );
+        }
+    }
+}
+
+#[derive(Component)]
+struct AchievementToast;
+
+/// Unlocks achievements as runs reach them, each only once, with a toast on the HUD.
+pub struct AchievementsPlugin;
+
+impl Plugin for AchievementsPlugin {
+    fn build(&self, app: &mut App) {
+        // After the main startup, so the HUD root exists
+        app.insert_resource(Achievements::load())
+            .add_startup_system(spawn_toast.in_base_set(StartupSet::PostStartup))
+            .add_system(check_achievements.in_set(OnUpdate(GameState::Playing)));
+    }
+}
+
+fn spawn_toast(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    hud: Query<Entity, With<HudRoot>>,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let (mut toast, flash) = Flash::bundle("", font, Color::GOLD, Val::Percent(80.));
+    toast.text.sections[0].style.font_size = 30.0;
+    toast.style.margin.left = Val::Percent(35.);
+    commands
+        .spawn((toast, flash, AchievementToast))
+        .set_parent(hud.single());
+}
+
+#[allow(clippy::too_many_arguments)]
+fn check_achievements(
+    config: Res<GameConfig>,
+    score: Res<Score>,
+    run_time: Res<RunTime>,
+    glide: Res<GlideStreak>,
+    autopilot: Res<AutopilotControl>,
+    comeback: Res<Comeback>,
+    pipe_rng: Res<PipeRng>,
+    daily: Option<Res<DailyChallenge>>,
+    language: Res<Language>,
+    mut achievements: ResMut<Achievements>,
+    mut toast: Query<(&mut Text, &mut Flash, &mut Visibility), With<AchievementToast>>,
+) {
+    // Nothing can end a zen run, so surviving one proves nothing. Neither does any run that
+    // wouldn't count for the high score
+    if !config.spawn_pipes
+        || !counts_for_records(&config, &autopilot, &comeback, &pipe_rng, daily.as_deref())
+    {
+        return;
+    }
+    for achievement in Achievement::ALL {
+        if achievements.is_unlocked(achievement)
+            || !achievement.reached(score.0, run_time.0, glide.run_best())
+        {
+            continue;
+        }
+        achievements.unlock(achievement);
+        info!(
+            "Achievement unlocked: {} ({})",
+            achievement.title(&language),
+            achievement.description(&language)
+        );
+        let (mut text, mut flash, mut visibility) = toast.single_mut();
+        text.sections[0].value =
+            language.format("achievement.unlocked", &[&achievement.title(&language)]);
+        flash.0.reset();
+        *visibility = Visibility::Visible;
+    }
+}
@@ -0,0 +1,78 @@
+use bevy::prelude::*;
+
+use crate::{config::SpriteAnimation, layers, GameConfig};
+
+/// Loops through the frames of a texture atlas. Purely visual: animated sprites carry no
+/// collider, so hitboxes stay those of the plain sprites they're drawn over.
+#[derive(Component)]
+pub struct AnimatedSprite {
+    frames: usize,
+    timer: Timer,
+}
+
+pub struct AnimationPlugin;
+
+impl Plugin for AnimationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(animate_sprites);
+    }
+}
+
+/// Loads the atlas described by `animation`, ready to be shared by every sprite using it.
+pub fn load_atlas(
+    animation: &SpriteAnimation,
+    asset_server: &AssetServer,
+    atlases: &mut Assets<TextureAtlas>,
+) -> Handle<TextureAtlas> {
+    atlases.add(TextureAtlas::from_grid(
+        asset_server.load(animation.texture.as_str()),
+        animation.tile_size,
+        animation.columns,
+        animation.rows,
+        None,
+        None,
+    ))
+}
+
+/// An animated sprite drawn just in front of its parent, mirrored vertically if `flip_y`.
+pub fn overlay(
+    animation: &SpriteAnimation,
+    atlas: Handle<TextureAtlas>,
+    flip_y: bool,
+) -> (SpriteSheetBundle, AnimatedSprite) {
+    (
+        SpriteSheetBundle {
+            texture_atlas: atlas,
+            sprite: TextureAtlasSprite {
+                flip_y,
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, layers::ANIMATION_OFFSET),
+            ..default()
+        },
+        AnimatedSprite {
+            frames: (animation.columns * animation.rows).max(1),
+            timer: Timer::from_seconds(1.0 / animation.fps.max(0.1), TimerMode::Repeating),
+        },
+    )
+}
+
+fn animate_sprites(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    mut sprites: Query<(&mut AnimatedSprite, &mut TextureAtlasSprite)>,
+) {
+    if config.reduce_motion {
+        return;
+    }
+    for (mut animation, mut sprite) in &mut sprites {
+        // Only touch the sprite when the frame actually changes, to keep change detection quiet
+        let advanced = animation
+            .timer
+            .tick(time.delta())
+            .times_finished_this_tick() as usize;
+        if advanced > 0 {
+            sprite.index = (sprite.index + advanced) % animation.frames;
+        }
+    }
+}
@@ -0,0 +1,176 @@
+use bevy::{input::InputSystem, prelude::*};
+
+use crate::{
+    config::KeyBindings, lang::Localized, GameState, HudRoot, Movable, NextPipe, Pipe, Player,
+};
+
+/// Flaps toward the next gap with a simple heuristic. The parameters let several
+/// AI birds behave slightly differently.
+#[derive(Component, Clone, Copy)]
+pub struct Autopilot {
+    /// Height relative to the gap centre the bird tries to stay above.
+    pub aim_offset: f32,
+    /// How far ahead, in seconds, the fall is extrapolated before deciding to flap.
+    pub lookahead: f32,
+}
+
+impl Default for Autopilot {
+    fn default() -> Self {
+        Self {
+            aim_offset: -30.0,
+            lookahead: 0.05,
+        }
+    }
+}
+
+impl Autopilot {
+    pub fn wants_flap(&self, bird_y: f32, velocity_y: f32, gap_y: f32) -> bool {
+        velocity_y <= 0.0 && bird_y + velocity_y * self.lookahead < gap_y + self.aim_offset
+    }
+}
+
+/// Who is flying the player's bird. The autopilot drives the same flap key the player does,
+/// so its flaps go through exactly the same path and config.
+#[derive(Resource, Default)]
+pub struct AutopilotControl {
+    pub active: bool,
+    // The autopilot flew at some point during the current run
+    assisted: bool,
+}
+
+impl AutopilotControl {
+    /// Whether the current run had any help, in which case it shouldn't count for records.
+    pub fn assisted(&self) -> bool {
+        self.assisted
+    }
+}
+
+#[derive(Component)]
+struct AutopilotLabel;
+
+/// Hands the bird to the autopilot and back with `KeyBindings::autopilot`, showing "AUTO"
+/// while it flies.
+pub struct AutopilotPlugin;
+
+impl Plugin for AutopilotPlugin {
+    fn build(&self, app: &mut App) {
+        // After the main startup, so the HUD root exists
+        app.init_resource::<AutopilotControl>()
+            .add_startup_system(spawn_label.in_base_set(StartupSet::PostStartup))
+            .add_system(start_run.in_schedule(OnEnter(GameState::Playing)))
+            .add_system(let_go.in_schedule(OnExit(GameState::Playing)))
+            // After the real input is read, so the autopilot has the last word
+            .add_systems(
+                (toggle, fly.after(toggle))
+                    .in_base_set(CoreSet::PreUpdate)
+                    .after(InputSystem)
+                    .distributive_run_if(in_state(GameState::Playing)),
+            )
+            .add_system(show_label.run_if(resource_changed::<AutopilotControl>()));
+    }
+}
+
+fn spawn_label(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    hud: Query<Entity, With<HudRoot>>,
+) {
+    commands
+        .spawn((
+            TextBundle {
+                visibility: Visibility::Hidden,
+                ..TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                        font_size: 30.0,
+                        color: Color::ORANGE_RED,
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        right: Val::Px(10.),
+                        top: Val::Px(50.),
+                        ..default()
+                    },
+                    ..default()
+                })
+            },
+            AutopilotLabel,
+            Localized("hud.autopilot"),
+        ))
+        .set_parent(hud.single());
+}
+
+fn start_run(mut control: ResMut<AutopilotControl>) {
+    control.assisted = control.active;
+}
+
+fn toggle(
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut control: ResMut<AutopilotControl>,
+) {
+    if !keyboard_input.just_pressed(bindings.autopilot) {
+        return;
+    }
+    control.active = !control.active;
+    control.assisted |= control.active;
+    if !control.active {
+        // Otherwise a flap held by the autopilot stays held for the player
+        keyboard_input.release(bindings.flap);
+    }
+}
+
+// Nothing releases the key the autopilot held while the run is over
+fn let_go(
+    control: Res<AutopilotControl>,
+    bindings: Res<KeyBindings>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+) {
+    if control.active {
+        keyboard_input.release(bindings.flap);
+    }
+}
+
+// Holds the flap key exactly while the autopilot wants to climb
+fn fly(
+    control: Res<AutopilotControl>,
+    next: Res<NextPipe>,
+    bindings: Res<KeyBindings>,
+    pipes: Query<(&Transform, &Pipe)>,
+    player: Query<(&Transform, &Movable), With<Player>>,
+    mut keyboard_input: ResMut<Input<KeyCode>>,
+) {
+    if !control.active {
+        return;
+    }
+    let Ok((transform, movable)) = player.get_single() else {
+        return;
+    };
+    let gap_y =
+        next.0
+            .and_then(|entity| pipes.get(entity).ok())
+            .map_or(0.0, |(pipe_transform, pipe)| {
+                pipe.nearest_opening(pipe_transform.translation.y, transform.translation.y)
+            });
+    if Autopilot::default().wants_flap(transform.translation.y, movable.velocity.y, gap_y) {
+        keyboard_input.press(bindings.flap);
+    } else {
+        keyboard_input.release(bindings.flap);
+    }
+}
+
+fn show_label(
+    control: Res<AutopilotControl>,
+    mut label: Query<&mut Visibility, With<AutopilotLabel>>,
+) {
+    if let Ok(mut visibility) = label.get_single_mut() {
+        *visibility = if control.active {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+}
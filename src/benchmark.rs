@@ -0,0 +1,94 @@
+use std::time::{Duration, Instant};
+
+use bevy::{app::AppExit, prelude::*, time::TimeUpdateStrategy};
+
+use crate::{autopilot::AutopilotControl, GameState, PipeRng};
+
+const DEFAULT_TICKS: usize = 3600;
+const SEED: u64 = 0x00C0_FFEE;
+const TICK: Duration = Duration::from_nanos(1_000_000_000 / 60);
+
+/// `--benchmark [ticks]` plays a fixed session and prints frame timings as JSON, then quits.
+/// Every run sees the same layout and inputs: saved settings are ignored, the seed is fixed, the
+/// player is flown by the autopilot, restarted on death, and game time advances by exactly
+/// 1/60 s per tick. Nothing it plays is counted or logged as the player's.
+pub struct BenchmarkPlugin {
+    ticks: usize,
+}
+
+impl BenchmarkPlugin {
+    pub fn from_args() -> Option<Self> {
+        let mut args = std::env::args().skip_while(|arg| arg != "--benchmark");
+        args.next()?;
+        let ticks = args
+            .next()
+            .and_then(|ticks| ticks.parse().ok())
+            .unwrap_or(DEFAULT_TICKS);
+        Some(Self { ticks })
+    }
+}
+
+impl Plugin for BenchmarkPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(Benchmark {
+            ticks: self.ticks,
+            frame_times: Vec::with_capacity(self.ticks),
+            last_frame: None,
+            deaths: 0,
+        })
+        .insert_resource(PipeRng::new(SEED, true))
+        .insert_resource(TimeUpdateStrategy::ManualDuration(TICK))
+        .add_startup_system(engage_autopilot)
+        .add_system(restart.in_set(OnUpdate(GameState::GameOver)))
+        .add_system(restart.in_set(OnUpdate(GameState::Victory)))
+        .add_system(measure.in_base_set(CoreSet::Last));
+    }
+}
+
+#[derive(Resource)]
+struct Benchmark {
+    ticks: usize,
+    // Wall-clock milliseconds between consecutive frames
+    frame_times: Vec<f32>,
+    last_frame: Option<Instant>,
+    deaths: u32,
+}
+
+fn engage_autopilot(mut control: ResMut<AutopilotControl>) {
+    control.active = true;
+}
+
+fn restart(mut benchmark: ResMut<Benchmark>, mut game_state: ResMut<NextState<GameState>>) {
+    benchmark.deaths += 1;
+    game_state.set(GameState::Playing);
+}
+
+fn measure(
+    mut benchmark: ResMut<Benchmark>,
+    entities: Query<Entity>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let now = Instant::now();
+    if let Some(last) = benchmark.last_frame.replace(now) {
+        benchmark
+            .frame_times
+            .push((now - last).as_secs_f32() * 1000.0);
+    }
+    if benchmark.frame_times.len() < benchmark.ticks {
+        return;
+    }
+
+    let frame_times = &benchmark.frame_times;
+    let average = frame_times.iter().sum::<f32>() / frame_times.len() as f32;
+    let max = frame_times.iter().copied().fold(0.0, f32::max);
+    let report = serde_json::json!({
+        "seed": SEED,
+        "ticks": frame_times.len(),
+        "avg_frame_ms": average,
+        "max_frame_ms": max,
+        "entities": entities.iter().count(),
+        "deaths": benchmark.deaths,
+    });
+    println!("{}", report);
+    exit.send(AppExit);
+}
@@ -0,0 +1,213 @@
+use bevy::prelude::*;
+use rand::rngs::StdRng;
+
+use crate::{
+    apply_velocity, config::KeyBindings, left_border, reuse_pipes, right_border, shape_pipe,
+    visible_size, Flash, GameConfig, GameState, GapEdge, GravityFlipPickup, HudRoot, Language,
+    Movable, NextPipe, Pipe, PipeRng, RunTime, Score, TunnelWall,
+};
+
+// Everything that decides the rest of a run's layout, as it was when the checkpoint was set
+struct Snapshot {
+    rng: StdRng,
+    looks: StdRng,
+    score: u32,
+    run_time: f32,
+    // Left to right from the pipe the next restart begins at, then the ones behind the bird
+    pipes: Vec<(Vec3, Pipe, Movable)>,
+    ahead: usize,
+}
+
+/// Practice mode's restart point. Restarting begins with the checkpoint's pipe scrolling in
+/// the way a run's first pipe does, followed by exactly the layout that came after it.
+#[derive(Resource, Default)]
+pub struct Checkpoint {
+    saved: Option<Snapshot>,
+    // The run just started and still needs the snapshot put back
+    pending: bool,
+}
+
+#[derive(Component)]
+struct CheckpointLabel;
+
+/// Sets a checkpoint at the upcoming pipe with `KeyBindings::checkpoint` while practicing, and
+/// clears it again from the game-over screen.
+pub struct CheckpointPlugin;
+
+impl Plugin for CheckpointPlugin {
+    fn build(&self, app: &mut App) {
+        // After the main startup, so the HUD root exists
+        app.init_resource::<Checkpoint>()
+            .add_startup_system(spawn_label.in_base_set(StartupSet::PostStartup))
+            .add_system(arm.in_schedule(OnEnter(GameState::Playing)))
+            .add_system(
+                restore
+                    .before(apply_velocity)
+                    .before(reuse_pipes)
+                    .in_set(OnUpdate(GameState::Playing))
+                    .run_if(|checkpoint: Res<Checkpoint>| checkpoint.pending),
+            )
+            .add_system(
+                set.after(restore)
+                    .in_set(OnUpdate(GameState::Playing))
+                    .run_if(|config: Res<GameConfig>| config.practice),
+            )
+            .add_system(clear.in_set(OnUpdate(GameState::GameOver)));
+    }
+}
+
+fn spawn_label(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    hud: Query<Entity, With<HudRoot>>,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    let (mut label, flash) = Flash::bundle("", font, Color::WHITE, Val::Percent(55.));
+    label.text.sections[0].style.font_size = 30.0;
+    label.style.margin.left = Val::Percent(40.);
+    commands
+        .spawn((label, flash, CheckpointLabel))
+        .set_parent(hud.single());
+}
+
+fn show(
+    label: &mut Query<(&mut Text, &mut Flash, &mut Visibility), With<CheckpointLabel>>,
+    message: String,
+) {
+    let (mut text, mut flash, mut visibility) = label.single_mut();
+    text.sections[0].value = message;
+    flash.0.reset();
+    *visibility = Visibility::Visible;
+}
+
+fn arm(config: Res<GameConfig>, mut checkpoint: ResMut<Checkpoint>) {
+    // Leaving practice forgets the checkpoint with it
+    if !config.practice {
+        checkpoint.saved = None;
+    }
+    checkpoint.pending = checkpoint.saved.is_some();
+}
+
+#[allow(clippy::too_many_arguments)]
+fn set(
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    language: Res<Language>,
+    pipe_rng: Res<PipeRng>,
+    score: Res<Score>,
+    run_time: Res<RunTime>,
+    next: Res<NextPipe>,
+    pipes: Query<(Entity, &Transform, &Pipe, &Movable)>,
+    mut checkpoint: ResMut<Checkpoint>,
+    mut label: Query<(&mut Text, &mut Flash, &mut Visibility), With<CheckpointLabel>>,
+) {
+    if !keyboard_input.just_pressed(bindings.checkpoint) {
+        return;
+    }
+    let Some((_, next_transform, _, _)) = next.0.and_then(|entity| pipes.get(entity).ok()) else {
+        return;
+    };
+    let next_x = next_transform.translation.x;
+    let mut saved: Vec<(Vec3, Pipe, Movable)> = pipes
+        .iter()
+        .map(|(_, transform, pipe, movable)| (transform.translation, pipe.clone(), movable.clone()))
+        .collect();
+    saved.sort_by(|(a, _, _), (b, _, _)| a.x.partial_cmp(&b.x).unwrap());
+    // Pipes already behind the bird come back after the checkpoint's, which is also where the
+    // rest of the run would have recycled them to
+    let first = saved
+        .iter()
+        .position(|(translation, _, _)| translation.x >= next_x)
+        .unwrap_or(0);
+    saved.rotate_left(first);
+    let ahead = saved.len() - first;
+    // The checkpoint's pipe may have scored already, but it's flown again from the restart
+    let mut score = score.0;
+    for (_, pipe, _) in &mut saved[..ahead] {
+        if pipe.passed {
+            pipe.passed = false;
+            score -= 1;
+        }
+    }
+
+    checkpoint.saved = Some(Snapshot {
+        rng: pipe_rng.rng.clone(),
+        looks: pipe_rng.looks.clone(),
+        score,
+        run_time: run_time.0,
+        pipes: saved,
+        ahead,
+    });
+    show(
+        &mut label,
+        language.format("checkpoint.set", &[&(score + 1)]),
+    );
+}
+
+fn clear(
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    language: Res<Language>,
+    mut checkpoint: ResMut<Checkpoint>,
+    mut label: Query<(&mut Text, &mut Flash, &mut Visibility), With<CheckpointLabel>>,
+) {
+    if !keyboard_input.just_pressed(bindings.checkpoint) || checkpoint.saved.is_none() {
+        return;
+    }
+    checkpoint.saved = None;
+    show(&mut label, language.t("checkpoint.cleared").to_string());
+}
+
+// Swaps the fresh layout `spawn_run` made for the snapshot, shifted so its first pipe enters
+// from the right. Pipes that were behind the bird go just past the left border, to be recycled
+// before anything else the way they would have been
+#[allow(clippy::too_many_arguments)]
+fn restore(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    mut checkpoint: ResMut<Checkpoint>,
+    mut pipe_rng: ResMut<PipeRng>,
+    mut score: ResMut<Score>,
+    mut run_time: ResMut<RunTime>,
+    mut pipes: Query<(&mut Transform, &mut Pipe, &mut Movable, &Children)>,
+    mut edges: Query<(&mut Transform, &GapEdge), Without<Pipe>>,
+    mut walls: Query<(Entity, &mut Visibility), (With<TunnelWall>, Without<GravityFlipPickup>)>,
+    mut pickups: Query<&mut Visibility, With<GravityFlipPickup>>,
+) {
+    checkpoint.pending = false;
+    let Some(snapshot) = &checkpoint.saved else {
+        return;
+    };
+    pipe_rng.rng = snapshot.rng.clone();
+    pipe_rng.looks = snapshot.looks.clone();
+    score.0 = snapshot.score;
+    run_time.0 = snapshot.run_time;
+
+    let width = visible_size(&config).x;
+    let start_x = right_border(width, config.offscreen_buffer);
+    let first_x = snapshot
+        .pipes
+        .first()
+        .map_or(0.0, |(translation, _, _)| translation.x);
+    let behind_x = left_border(width, config.offscreen_buffer) - 1.0;
+    for (
+        i,
+        ((mut transform, mut pipe, mut movable, children), (translation, saved, saved_movable)),
+    ) in pipes.iter_mut().zip(&snapshot.pipes).enumerate()
+    {
+        transform.translation = *translation;
+        transform.translation.x = if i < snapshot.ahead {
+            translation.x - first_x + start_x
+        } else {
+            behind_x
+        };
+        *pipe = saved.clone();
+        *movable = saved_movable.clone();
+        shape_pipe(&mut commands, &pipe, children, &mut edges, &mut walls);
+        // Pickups aren't part of the snapshot, they're the one thing a checkpoint skips
+        let mut pipe_pickups = pickups.iter_many_mut(children.iter());
+        while let Some(mut visibility) = pipe_pickups.fetch_next() {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
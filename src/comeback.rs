@@ -0,0 +1,82 @@
+use bevy::prelude::*;
+
+use crate::{daily::DailyChallenge, save_high_score, survival, GameConfig, GameState, Score};
+
+/// How much the comeback rubber-band is easing play, from 0 (not at all) to 1 (the full
+/// `comeback_max_*` limits). It rises a step after `comeback_deaths` low-scoring deaths in a row
+/// and falls a step with every run that gets past `comeback_low_score`. Never outside of
+/// `GameConfig::comeback`, and never during daily challenges.
+#[derive(Resource, Default)]
+pub struct Comeback {
+    level: f32,
+    // Low-scoring deaths in a row since the level last rose
+    low_streak: usize,
+}
+
+impl Comeback {
+    /// Whether runs are being made easier right now. Eased runs don't count for records.
+    pub fn easing(&self, config: &GameConfig) -> bool {
+        config.comeback && self.level > 0.0
+    }
+
+    /// Extra height every pipe opening gets.
+    pub fn gap_bonus(&self, config: &GameConfig) -> f32 {
+        if config.comeback {
+            self.level * config.comeback_max_gap
+        } else {
+            0.0
+        }
+    }
+
+    /// Multiplier for the pipes' speed and acceleration.
+    pub fn speed_factor(&self, config: &GameConfig) -> f32 {
+        if config.comeback {
+            1.0 - self.level * config.comeback_max_slowdown.clamp(0.0, 1.0)
+        } else {
+            1.0
+        }
+    }
+}
+
+pub struct ComebackPlugin;
+
+impl Plugin for ComebackPlugin {
+    fn build(&self, app: &mut App) {
+        // After the records, which go by the level the run was played at
+        app.init_resource::<Comeback>()
+            .add_system(
+                track_runs
+                    .after(save_high_score)
+                    .after(survival::record)
+                    .in_schedule(OnEnter(GameState::GameOver)),
+            )
+            .add_system(
+                track_runs
+                    .after(save_high_score)
+                    .after(survival::record)
+                    .in_schedule(OnEnter(GameState::Victory)),
+            );
+    }
+}
+
+// A won run is as good a run as any, and eases off like one
+fn track_runs(
+    config: Res<GameConfig>,
+    score: Res<Score>,
+    daily: Option<Res<DailyChallenge>>,
+    mut comeback: ResMut<Comeback>,
+) {
+    if !config.comeback || daily.is_some() || config.practice {
+        return;
+    }
+    if score.0 > config.comeback_low_score {
+        comeback.low_streak = 0;
+        comeback.level = (comeback.level - config.comeback_step).max(0.0);
+    } else {
+        comeback.low_streak += 1;
+        if comeback.low_streak >= config.comeback_deaths {
+            comeback.low_streak = 0;
+            comeback.level = (comeback.level + config.comeback_step).min(1.0);
+        }
+    }
+}
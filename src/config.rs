@@ -0,0 +1,532 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::speed_curve::SpeedCurve;
+
+/// Multisampling levels exposed to players. Bevy 0.10 only supports 1 and 4 samples.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum MsaaSetting {
+    #[default]
+    Off,
+    X4,
+}
+
+impl From<MsaaSetting> for Msaa {
+    fn from(setting: MsaaSetting) -> Self {
+        match setting {
+            MsaaSetting::Off => Msaa::Off,
+            MsaaSetting::X4 => Msaa::Sample4,
+        }
+    }
+}
+
+/// Shape the bird collides as. Pipes and the ground are always boxes.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum BirdHitbox {
+    /// The bird's sprite rectangle.
+    Box,
+    /// A circle around the sprite's centre, which forgives clipping a corner.
+    Circle { radius: f32 },
+}
+
+/// Sets of config flags a run can be played with, switched between with
+/// [`KeyBindings::game_mode`]. The daily challenge is separate, as it only fixes the seed.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum GameMode {
+    #[default]
+    Classic,
+    /// Relaxed flying with nothing to dodge.
+    Zen,
+    /// Drilling hard sections from a checkpoint, with nothing counting for records.
+    Practice,
+}
+
+impl GameMode {
+    pub const ALL: [GameMode; 3] = [GameMode::Classic, GameMode::Zen, GameMode::Practice];
+
+    pub fn next(self) -> Self {
+        let i = Self::ALL.iter().position(|mode| *mode == self).unwrap_or(0);
+        Self::ALL[(i + 1) % Self::ALL.len()]
+    }
+
+    /// Key of the mode's name in the language files.
+    pub fn name_key(self) -> &'static str {
+        match self {
+            GameMode::Classic => "mode.classic",
+            GameMode::Zen => "mode.zen",
+            GameMode::Practice => "mode.practice",
+        }
+    }
+
+    pub fn apply(self, config: &mut GameConfig) {
+        config.spawn_pipes = self != GameMode::Zen;
+        config.practice = self == GameMode::Practice;
+    }
+}
+
+/// The part of a pipe the bird has to get past for the point. Pipes move left, so their
+/// leading edge is the left one.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ScoreTrigger {
+    /// As the bird enters the pipe.
+    LeadingEdge,
+    /// Halfway through, the classic feel.
+    Center,
+    /// Only once the bird is all the way out.
+    TrailingEdge,
+}
+
+/// Config a stage of a staged run switches to. Whatever a stage leaves unset stays as the
+/// stage before had it.
+#[derive(Clone, Debug, Default)]
+pub struct StageOverrides {
+    pub gap_size: Option<f32>,
+    pub tunnel_chance: Option<f64>,
+    /// Colour multiplied into the background sprites.
+    pub background_tint: Option<Color>,
+}
+
+impl StageOverrides {
+    /// Everything a stage can override, as `config` has it now.
+    pub fn capture(config: &GameConfig) -> Self {
+        Self {
+            gap_size: Some(config.gap_size),
+            tunnel_chance: Some(config.tunnel_chance),
+            background_tint: Some(config.background_tint),
+        }
+    }
+
+    pub fn apply(&self, config: &mut GameConfig) {
+        if let Some(gap_size) = self.gap_size {
+            config.gap_size = gap_size;
+        }
+        if let Some(tunnel_chance) = self.tunnel_chance {
+            config.tunnel_chance = tunnel_chance;
+        }
+        if let Some(background_tint) = self.background_tint {
+            config.background_tint = background_tint;
+        }
+    }
+}
+
+/// A looping animation laid out as a grid of equally sized frames in one texture.
+#[derive(Clone, Debug)]
+pub struct SpriteAnimation {
+    /// Asset path of the texture holding the frames.
+    pub texture: String,
+    pub tile_size: Vec2,
+    pub columns: usize,
+    pub rows: usize,
+    /// Frames per second.
+    pub fps: f32,
+}
+
+#[derive(Resource)]
+pub struct GameConfig {
+    /// Pixels shaved off the pipe hitbox around the gap opening, so clipping
+    /// the very corner of a gap is survivable. Zero keeps hitboxes flush with the sprites.
+    pub gap_edge_forgiveness: f32,
+    /// Off by default: the pixel-art sprites don't benefit from it.
+    pub msaa: MsaaSetting,
+    /// Turns off purely decorative motion such as squash-and-stretch.
+    pub reduce_motion: bool,
+    /// How much the bird stretches vertically on a flap, as a fraction of its size.
+    pub squash_stretch: f32,
+    /// One knob over every decorative effect, from 0 to 1: particle counts and the squash and
+    /// stretch are scaled by it, and 0 is the same as `reduce_motion`.
+    pub effects_intensity: f32,
+    /// Background scroll speed in pixels per second while pipes move at their start speed.
+    pub background_speed: f32,
+    /// Scale the background speed with the pipes as they accelerate instead of keeping it constant.
+    pub background_speed_follows_pipes: bool,
+    /// How far away the background looks when the camera moves: the share of the camera's
+    /// movement it shows on screen. 1 moves it with the world, 0 pins it to the camera.
+    pub background_parallax: f32,
+    /// Runs the whole game slower for the session, to give new players more time to react.
+    pub assist_mode: bool,
+    /// Values above 1 zoom in and show less of the playfield. Clamped to a sane range on use.
+    pub camera_zoom: f32,
+    /// Training aid highlighting the gap of the next pipe.
+    pub gap_guide: bool,
+    /// Novelty pickup that turns gravity upside down for `gravity_flip_duration` seconds.
+    pub gravity_flip_powerup: bool,
+    pub gravity_flip_duration: f32,
+    /// When false pipes keep moving at their start speed forever instead of ramping up.
+    pub pipe_acceleration_enabled: bool,
+    /// Number of AI birds flying alongside the player, to stress-test many agents at once.
+    #[cfg(feature = "debug")]
+    pub flock_size: usize,
+    /// Hides optional HUD elements such as the speed indicator.
+    pub hud_minimal: bool,
+    /// Speed the soundtrack up slightly as the pipes accelerate.
+    pub music_tempo_scaling: bool,
+    /// Survive the first death of a session once, outside daily challenges.
+    pub first_death_mercy: bool,
+    /// Faint marker at the right screen edge showing the height of the gap about to come into view.
+    pub gap_telegraph: bool,
+    /// Minimum seconds between accepted flaps, to discourage mashing. Zero is the classic feel.
+    pub flap_cooldown: f32,
+    /// Zen mode turns this off: no pipes and no score, only the screen edges to bump into.
+    pub spawn_pipes: bool,
+    /// Size of the puff burst on death. The particles are pooled, so this also caps how many exist.
+    pub death_particles: usize,
+    /// Pull the bird down from the moment it spawns, instead of hovering until the first flap.
+    /// The pipes and the run's clock wait for that flap too.
+    pub gravity_from_spawn: bool,
+    /// Lean the bird along its full velocity relative to the world, including the forward
+    /// scroll, rather than tilting by vertical speed alone.
+    pub tilt_follows_velocity: bool,
+    pub window_title: String,
+    /// Tint pipes by how fast they're going when they come back around, to telegraph the ramp.
+    pub pipe_tint_by_speed: bool,
+    /// Evenly spaced from start to max speed, multiplied with the pipe sprites.
+    pub pipe_tint_stops: Vec<Color>,
+    /// Hardcore modifier: pixels taken off the gap of every recycled pipe per point scored.
+    /// Zero keeps the classic fixed gap.
+    pub gap_shrink_per_point: f32,
+    /// Smallest gap shrinking can reach. Never tighter than the bird itself, whatever this says.
+    pub min_gap: f32,
+    /// Height of the scrolling ground from the bottom of the screen. Its top edge is also the
+    /// lethal floor, so the art and the death line always agree.
+    pub ground_height: f32,
+    /// Training aid dotting out where the bird will go if it doesn't flap again.
+    pub trajectory_preview: bool,
+    /// Put the first gap of a run level with the bird's start, so a run never opens on a bad draw.
+    pub center_first_pipe: bool,
+    /// Always skip decorative effects, for low-end machines.
+    pub performance_mode: bool,
+    /// Skip decorative effects by themselves while the frame rate stays low.
+    pub auto_performance_mode: bool,
+    /// Keep the playfield at `design_aspect_ratio` with bars around it, whatever the window shape,
+    /// so pipe spacing feels the same everywhere.
+    pub letterbox: bool,
+    /// Width over height of the letterboxed playfield.
+    pub design_aspect_ratio: f32,
+    /// Gentle sine bob of the bird while it hovers before the first flap, in pixels and cycles
+    /// per second. Zero amplitude holds it still; reduce motion turns it off as well.
+    pub hover_bob_amplitude: f32,
+    pub hover_bob_speed: f32,
+    /// Range the horizontal distance between consecutive pipes is drawn from. Equal values keep
+    /// the classic metronomic spacing; either way pipes never get closer than two pipe widths.
+    pub pipe_spacing_min: f32,
+    pub pipe_spacing_max: f32,
+    /// Fraction of the bird's theoretical climb or fall between two pipes that consecutive gaps
+    /// may differ by. Lower is more forgiving; 1 allows only perfect play.
+    pub gap_reach_margin: f32,
+    /// HUD variant for streaming: an oversized score in the corner plus this session's
+    /// achievements, in place of the regular score.
+    pub streamer_mode: bool,
+    /// Downward acceleration of the bird, in pixels per second squared (negative is down).
+    pub gravity: f32,
+    /// Upward speed a flap sets the bird to.
+    pub flap_speed: f32,
+    /// Height of a pipe's opening before any hardcore shrinking.
+    pub gap_size: f32,
+    /// Animated frames drawn over the pipe starts, for themed pipes such as lava or electric.
+    /// Frames should match the `sprites/pipe.png` size, as the hitbox still comes from that.
+    pub pipe_start_animation: Option<SpriteAnimation>,
+    /// Counts runs per session and across sessions, shown briefly as each run starts and on
+    /// the game-over screen.
+    pub attempt_counter: bool,
+    /// Training aid drawing the next pipe as a faint ghost at the right screen edge, fading in
+    /// until the real pipe arrives to take its place.
+    pub ghost_pipe: bool,
+    /// Shift the camera slightly ahead of the bird as the pipes speed up, to show a little more
+    /// of what's coming. Off under reduce motion.
+    pub camera_lookahead: bool,
+    /// How far past the view edges pipes spawn and get recycled. Larger values place pipes
+    /// earlier, which avoids pop-in at high speeds.
+    pub offscreen_buffer: f32,
+    /// Draw the sky with an animated shader, scrolling clouds over a vertical gradient, instead of
+    /// the static background. Needs the `sky_shader` feature.
+    #[cfg(feature = "sky_shader")]
+    pub sky_shader: bool,
+    #[cfg(feature = "sky_shader")]
+    pub sky_top_color: Color,
+    #[cfg(feature = "sky_shader")]
+    pub sky_bottom_color: Color,
+    /// Its alpha sets how opaque the thickest clouds are.
+    #[cfg(feature = "sky_shader")]
+    pub sky_cloud_color: Color,
+    /// Cloud drift in pixels per second.
+    #[cfg(feature = "sky_shader")]
+    pub sky_scroll_speed: f32,
+    /// Like `background_parallax`, for the clouds.
+    #[cfg(feature = "sky_shader")]
+    pub sky_parallax: f32,
+    /// Thin bar under the score filling up toward the next milestone of `MILESTONE_PIPES` pipes.
+    pub progress_bar: bool,
+    /// Outline the bird's hitbox and the one it hit for a moment after a death, to make
+    /// unfair-looking deaths easy to understand and report.
+    pub show_fatal_hitboxes: bool,
+    /// Custom pipe speed progression replacing the linear ramp. None keeps the ramp.
+    pub speed_curve: Option<SpeedCurve>,
+    /// Score that wins the run and ends it on a victory screen. None plays on endlessly.
+    pub win_score: Option<u32>,
+    /// Shape the bird collides with pipes as. A circle is more forgiving on corners than the
+    /// sprite's box.
+    pub bird_hitbox: BirdHitbox,
+    /// Folder under `assets` with replacement sprites, laid out like `assets` itself. Anything
+    /// it lacks comes from the default sprites.
+    pub theme: Option<String>,
+    /// Let runs on a locked seed, including replayed share codes, set a new best. Off by default,
+    /// since practicing one layout until it's mastered isn't a fair comparison.
+    pub locked_seed_counts_for_best: bool,
+    /// Draw a lip over the gap end of every pipe, for depth. Only themes that provide a
+    /// `sprites/pipe_cap.png` have one; the default sprites don't.
+    pub pipe_caps: bool,
+    /// Log how long each flap takes from the key being polled to being applied, with a rolling
+    /// average in the corner, for tuning responsiveness.
+    #[cfg(feature = "debug")]
+    pub input_latency_diagnostics: bool,
+    /// A line on the game-over screen naming what ended the run.
+    pub death_message: bool,
+    /// Chance a recycled pipe comes back as a tunnel: two narrow openings split by a solid
+    /// middle, forcing a choice between the top and bottom path. Zero never spawns any.
+    pub tunnel_chance: f64,
+    /// Height of each of a tunnel's openings. Never tighter than the bird itself.
+    pub tunnel_gap: f32,
+    /// Small puffs left below the bird on each flap, and how big they are. Zero turns them off;
+    /// reduce motion does too.
+    pub flap_particles: usize,
+    pub flap_particle_size: f32,
+    /// Seconds the screen takes to ease between the darkness of play and of the end screens.
+    /// Zero cuts straight across, as reduce motion does.
+    pub transition_duration: f32,
+    /// When passing a pipe scores, compared against the bird's centre.
+    pub score_trigger: ScoreTrigger,
+    /// Most decorative sprites, such as particles, shown at once. Effects past it are skipped,
+    /// and performance mode allows only a quarter of it.
+    pub visual_budget: usize,
+    /// Extreme easy mode for young players: flaps by itself whenever the bird is about to hit
+    /// the ground, so only the pipes end a run. Runs with it don't count for the best, the daily
+    /// challenge or achievements.
+    pub ground_assist: bool,
+    /// Append every finished run to `runs.csv`, for tracking progress in a spreadsheet.
+    pub run_log: bool,
+    /// Quietly eases runs for a player who keeps dying early: after `comeback_deaths` deaths in a
+    /// row at `comeback_low_score` or less, the next runs get a step wider openings and slower
+    /// pipes, taken back off a step with each better run. Off by default, and never in daily
+    /// challenges.
+    pub comeback: bool,
+    pub comeback_deaths: usize,
+    pub comeback_low_score: u32,
+    /// Fraction of the limits below added or removed per step.
+    pub comeback_step: f32,
+    /// Most extra opening height, in pixels.
+    pub comeback_max_gap: f32,
+    /// Most the pipes slow down, as a fraction of their speed.
+    pub comeback_max_slowdown: f32,
+    /// Scale all UI, text included, with the window so it reads the same at 720p and 4K. At
+    /// `ui_design_height` logical pixels of playfield height everything is its authored size.
+    pub scale_ui: bool,
+    pub ui_design_height: f32,
+    /// Seconds the restart key has to be held on the end screens before a new run starts, so a
+    /// stray press can't skip past the score. Zero restarts on the press, and quick restart
+    /// always does.
+    pub restart_hold: f32,
+    /// Extra pipe looks, each pipe picking one or the default at random from the seed. A name
+    /// `n` loads `sprites/pipe_<n>.png` and `sprites/pipe_piece_<n>.png` from the theme or the
+    /// defaults, and is left out unless both exist. Variants are drawn at the default pipe size
+    /// and keep its hitbox, so they never change how a seed plays.
+    pub pipe_variants: Vec<String>,
+    /// Replay the last seconds before a death in slow motion over the game-over screen. Restart
+    /// skips it; reduce motion plays it at normal speed.
+    pub instant_replay: bool,
+    /// Floatier flaps: near the top of a flap, while the bird moves slower than
+    /// `apex_hang_speed`, gravity eases off by up to `apex_hang` (0 to 1) of itself, for at most
+    /// `apex_hang_time` seconds per flap. Zero keeps the classic constant gravity.
+    pub apex_hang: f32,
+    pub apex_hang_speed: f32,
+    pub apex_hang_time: f32,
+    /// Set by practice mode: restarts can begin from a checkpoint, and nothing counts for the
+    /// best, the daily challenge, achievements or the comeback.
+    pub practice: bool,
+    /// Colour multiplied into the background sprites.
+    pub background_tint: Color,
+    /// Splits endless runs into stages of `stage_size` pipes, zero keeping one endless stage.
+    /// Each stage past the first applies the next of `stages`, announced by a banner; once
+    /// they run out, the last one carries on.
+    pub stage_size: u32,
+    pub stages: Vec<StageOverrides>,
+    /// Send the dead bird spinning down to the ground behind the game-over screen, at up to
+    /// `death_tumble_spin` radians per second. Reduce motion leaves it where it died.
+    pub death_tumble: bool,
+    pub death_tumble_spin: f32,
+    /// Load every sprite runs are built from at startup and keep them loaded, holding the first
+    /// flap until they're in, so neither it nor a restart waits on the disk.
+    pub preload_assets: bool,
+    /// Keep a record of the longest time survived in a run next to the high score, shown on the
+    /// game-over screen.
+    pub survival_record: bool,
+    /// Smoother flaps: `flap_smoothing` (0 to 1) of the change in speed a flap makes is spread
+    /// over `flap_smoothing_time` seconds as a strong upward push instead of being set at once.
+    /// Zero time keeps the classic instant flap.
+    pub flap_smoothing: f32,
+    pub flap_smoothing_time: f32,
+}
+
+impl GameConfig {
+    pub fn effects_intensity(&self) -> f32 {
+        self.effects_intensity.clamp(0.0, 1.0)
+    }
+}
+
+impl Default for GameConfig {
+    fn default() -> Self {
+        Self {
+            gap_edge_forgiveness: 0.0,
+            msaa: MsaaSetting::Off,
+            reduce_motion: false,
+            squash_stretch: 0.25,
+            effects_intensity: 1.0,
+            background_speed: 50.0,
+            background_speed_follows_pipes: true,
+            background_parallax: 0.5,
+            assist_mode: false,
+            camera_zoom: 1.0,
+            gap_guide: false,
+            gravity_flip_powerup: false,
+            gravity_flip_duration: 5.0,
+            pipe_acceleration_enabled: true,
+            #[cfg(feature = "debug")]
+            flock_size: 0,
+            hud_minimal: false,
+            music_tempo_scaling: true,
+            first_death_mercy: false,
+            gap_telegraph: false,
+            flap_cooldown: 0.0,
+            spawn_pipes: true,
+            death_particles: 12,
+            gravity_from_spawn: false,
+            tilt_follows_velocity: false,
+            window_title: "Flappy Bird".to_string(),
+            pipe_tint_by_speed: false,
+            pipe_tint_stops: vec![
+                Color::WHITE,
+                Color::rgb(1.0, 0.8, 0.5),
+                Color::rgb(1.0, 0.55, 0.5),
+            ],
+            gap_shrink_per_point: 0.0,
+            min_gap: 160.0,
+            ground_height: 80.0,
+            trajectory_preview: false,
+            center_first_pipe: true,
+            performance_mode: false,
+            auto_performance_mode: true,
+            letterbox: false,
+            design_aspect_ratio: 16.0 / 9.0,
+            hover_bob_amplitude: 12.0,
+            hover_bob_speed: 0.8,
+            pipe_spacing_min: crate::PIPE_GAP,
+            pipe_spacing_max: crate::PIPE_GAP,
+            gap_reach_margin: 0.8,
+            streamer_mode: false,
+            gravity: crate::GRAVITY,
+            flap_speed: crate::UP_SPEED,
+            gap_size: crate::PIPE_WINDOW_SIZE,
+            pipe_start_animation: None,
+            attempt_counter: true,
+            ghost_pipe: false,
+            camera_lookahead: false,
+            offscreen_buffer: 100.0,
+            #[cfg(feature = "sky_shader")]
+            sky_shader: true,
+            #[cfg(feature = "sky_shader")]
+            sky_top_color: Color::rgb(0.3, 0.6, 0.95),
+            #[cfg(feature = "sky_shader")]
+            sky_bottom_color: Color::rgb(0.75, 0.9, 1.0),
+            #[cfg(feature = "sky_shader")]
+            sky_cloud_color: Color::rgba(1.0, 1.0, 1.0, 0.8),
+            #[cfg(feature = "sky_shader")]
+            sky_scroll_speed: 20.0,
+            #[cfg(feature = "sky_shader")]
+            sky_parallax: 0.2,
+            progress_bar: false,
+            show_fatal_hitboxes: false,
+            speed_curve: None,
+            win_score: None,
+            bird_hitbox: BirdHitbox::Box,
+            theme: None,
+            locked_seed_counts_for_best: false,
+            pipe_caps: true,
+            #[cfg(feature = "debug")]
+            input_latency_diagnostics: false,
+            death_message: true,
+            tunnel_chance: 0.0,
+            tunnel_gap: 170.0,
+            flap_particles: 3,
+            flap_particle_size: 12.0,
+            transition_duration: 0.3,
+            score_trigger: ScoreTrigger::Center,
+            visual_budget: 64,
+            ground_assist: false,
+            run_log: false,
+            comeback: false,
+            comeback_deaths: 3,
+            comeback_low_score: 3,
+            comeback_step: 0.25,
+            comeback_max_gap: 40.0,
+            comeback_max_slowdown: 0.2,
+            scale_ui: true,
+            ui_design_height: 720.0,
+            restart_hold: 0.0,
+            pipe_variants: Vec::new(),
+            instant_replay: false,
+            apex_hang: 0.0,
+            apex_hang_speed: 200.0,
+            apex_hang_time: 0.15,
+            practice: false,
+            background_tint: Color::WHITE,
+            stage_size: 0,
+            stages: Vec::new(),
+            death_tumble: false,
+            death_tumble_spin: 10.0,
+            preload_assets: true,
+            survival_record: true,
+            flap_smoothing: 0.5,
+            flap_smoothing_time: 0.0,
+        }
+    }
+}
+
+#[derive(Resource, Clone, PartialEq, Debug, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KeyBindings {
+    pub flap: KeyCode,
+    pub restart: KeyCode,
+    /// Restarts straight from the game-over phase.
+    pub quick_restart: KeyCode,
+    /// Pressed twice in a row, puts every setting back to its default.
+    pub reset_settings: KeyCode,
+    /// Hands the bird to the autopilot, or takes it back.
+    pub autopilot: KeyCode,
+    /// Keeps replaying the current layout on every restart, or goes back to random ones.
+    pub seed_lock: KeyCode,
+    /// Switches to the next language in `assets/lang`.
+    pub language: KeyCode,
+    /// Starts over in the next game mode.
+    pub game_mode: KeyCode,
+    /// Steps the effects intensity down a quarter, wrapping from none back to full.
+    pub effects_intensity: KeyCode,
+    /// In practice, makes the next restart begin at the upcoming pipe. On the game-over screen,
+    /// clears it to start from the beginning again.
+    pub checkpoint: KeyCode,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        Self {
+            flap: KeyCode::Space,
+            restart: KeyCode::Space,
+            quick_restart: KeyCode::R,
+            reset_settings: KeyCode::F9,
+            autopilot: KeyCode::F6,
+            seed_lock: KeyCode::L,
+            language: KeyCode::F4,
+            game_mode: KeyCode::F5,
+            effects_intensity: KeyCode::F3,
+            checkpoint: KeyCode::C,
+        }
+    }
+}
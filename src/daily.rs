@@ -0,0 +1,84 @@
+use std::{
+    collections::BTreeMap,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use bevy::prelude::*;
+
+use crate::save;
+
+const DAILY_SCORES_PATH: &str = "daily_scores.txt";
+
+/// Present only during a daily challenge run. Days are counted since the Unix epoch in UTC.
+#[derive(Resource)]
+pub struct DailyChallenge {
+    pub day: u64,
+    // Best score per day, stored as "<day> <score>" lines
+    scores: BTreeMap<u64, u32>,
+}
+
+impl DailyChallenge {
+    pub fn today() -> Self {
+        let day = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_secs() / (24 * 60 * 60))
+            .unwrap_or_default();
+        let scores = std::fs::read_to_string(DAILY_SCORES_PATH)
+            .map(|contents| {
+                contents
+                    .lines()
+                    .filter_map(|line| {
+                        let (day, score) = line.split_once(' ')?;
+                        Some((day.parse().ok()?, score.parse().ok()?))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { day, scores }
+    }
+
+    /// Everyone playing on the same UTC day gets the same seed.
+    pub fn seed(&self) -> u64 {
+        // Spread consecutive days apart so their layouts don't look related
+        self.day.wrapping_mul(0x9E37_79B9_7F4A_7C15)
+    }
+
+    pub fn best(&self) -> Option<u32> {
+        self.scores.get(&self.day).copied()
+    }
+
+    pub fn record(&mut self, score: u32) {
+        if self.best().is_some_and(|best| score <= best) {
+            return;
+        }
+        self.scores.insert(self.day, score);
+
+        let contents: String = self
+            .scores
+            .iter()
+            .map(|(day, score)| format!("{} {}\n", day, score))
+            .collect();
+        if let Err(err) = save::write_atomic(DAILY_SCORES_PATH, contents) {
+            error!(
+                "Failed to save daily scores to {}: {}",
+                DAILY_SCORES_PATH, err
+            );
+        }
+    }
+
+    /// The challenge's date as YYYY-MM-DD.
+    pub fn date(&self) -> String {
+        // Days to civil date, from Howard Hinnant's date algorithms
+        let z = self.day as i64 + 719_468;
+        let era = z.div_euclid(146_097);
+        let day_of_era = z.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        let mp = (5 * day_of_year + 2) / 153;
+        let day = day_of_year - (153 * mp + 2) / 5 + 1;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 };
+        let year = year_of_era + era * 400 + i64::from(month <= 2);
+        format!("{:04}-{:02}-{:02}", year, month, day)
+    }
+}
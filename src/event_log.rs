@@ -0,0 +1,98 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{CollisionEvent, FlapEvent, Pipe, Score};
+
+const EVENT_LOG_CAPACITY: usize = 256;
+const EVENT_LOG_PATH: &str = "event_log.txt";
+const DUMP_KEY: KeyCode = KeyCode::F8;
+
+/// The last few hundred gameplay events, oldest first, so a report like "the bird died for
+/// no reason" can be replayed from what actually happened.
+#[derive(Resource, Default)]
+pub struct EventLog {
+    entries: VecDeque<(f32, String)>,
+}
+
+impl EventLog {
+    fn push(&mut self, time: &Time, entry: String) {
+        if self.entries.len() == EVENT_LOG_CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back((time.elapsed_seconds(), entry));
+    }
+}
+
+/// Records flaps, scores, pipe recycles and collisions, and writes them to `event_log.txt`
+/// when F8 is pressed.
+pub struct EventLogPlugin;
+
+impl Plugin for EventLogPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<EventLog>().add_systems(
+            (
+                record_flaps,
+                record_collisions,
+                record_scores.run_if(resource_changed::<Score>()),
+                record_pipes,
+                dump_log,
+            )
+                .chain(),
+        );
+    }
+}
+
+fn record_flaps(time: Res<Time>, mut flaps: EventReader<FlapEvent>, mut log: ResMut<EventLog>) {
+    for flap in flaps.iter() {
+        log.push(&time, format!("Flap at y={:.1}", flap.y));
+    }
+}
+
+fn record_collisions(
+    time: Res<Time>,
+    mut collisions: EventReader<CollisionEvent>,
+    mut log: ResMut<EventLog>,
+) {
+    for collision in collisions.iter() {
+        log.push(&time, format!("Collision with {:?}", collision.kind));
+    }
+}
+
+fn record_scores(time: Res<Time>, score: Res<Score>, mut log: ResMut<EventLog>) {
+    log.push(&time, format!("Score {}", score.0));
+}
+
+// Spawned and recycled pipes only, the same way `tint_pipes` tells them apart from scoring
+fn record_pipes(
+    time: Res<Time>,
+    pipes: Query<(&Transform, &Pipe), Changed<Pipe>>,
+    mut log: ResMut<EventLog>,
+) {
+    for (transform, pipe) in &pipes {
+        if !pipe.passed {
+            log.push(
+                &time,
+                format!(
+                    "Pipe at x={:.1} gap y={:.1} height={:.1}",
+                    transform.translation.x, transform.translation.y, pipe.gap
+                ),
+            );
+        }
+    }
+}
+
+fn dump_log(keyboard_input: Res<Input<KeyCode>>, log: Res<EventLog>) {
+    if !keyboard_input.just_pressed(DUMP_KEY) {
+        return;
+    }
+    let contents: String = log
+        .entries
+        .iter()
+        .map(|(time, entry)| format!("[{:9.3}s] {}\n", time, entry))
+        .collect();
+    match std::fs::write(EVENT_LOG_PATH, contents) {
+        Ok(()) => info!("Wrote {} events to {}", log.entries.len(), EVENT_LOG_PATH),
+        Err(err) => error!("Failed to write event log to {}: {}", EVENT_LOG_PATH, err),
+    }
+}
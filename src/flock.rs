@@ -0,0 +1,156 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    autopilot::Autopilot, find_collision, flap, layers, visible_bounds, Colliders, GameConfig,
+    GameState, HudRoot, Movable, NextPipe, Pipe, RunEntity, RunTime, Theme,
+};
+
+#[derive(Component)]
+struct FlockBird;
+
+#[derive(Component)]
+struct FlockLabel;
+
+/// Spawns `GameConfig::flock_size` autopilot birds next to the player at the start of every run.
+/// They collide with pipes like the player does but never end the run.
+pub struct FlockPlugin;
+
+impl Plugin for FlockPlugin {
+    fn build(&self, app: &mut App) {
+        // After the main startup, so the HUD root exists
+        app.add_startup_system(spawn_flock_label.in_base_set(StartupSet::PostStartup))
+            .add_system(spawn_flock.in_schedule(OnEnter(GameState::Playing)))
+            .add_systems(
+                (steer_flock, kill_flock_birds, update_flock_label)
+                    .chain()
+                    .in_set(OnUpdate(GameState::Playing)),
+            );
+    }
+}
+
+// Part of the run, so `reset_run` clears whatever is left of the last flock
+fn spawn_flock(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut theme: ResMut<Theme>,
+    config: Res<GameConfig>,
+) {
+    if config.flock_size == 0 {
+        return;
+    }
+
+    let texture = theme.image(&asset_server, "sprites/bird.png");
+    let mut rng = rand::thread_rng();
+    for _ in 0..config.flock_size {
+        commands.spawn((
+            SpriteBundle {
+                texture: texture.clone(),
+                sprite: Sprite {
+                    color: Color::rgba(1.0, 1.0, 1.0, 0.5),
+                    ..default()
+                },
+                transform: Transform::from_xyz(0.0, rng.gen_range(-100.0..100.0), layers::AI_BIRDS),
+                ..default()
+            },
+            Movable {
+                acceleration: Vec3::Y * config.gravity,
+                ..default()
+            },
+            Autopilot {
+                aim_offset: rng.gen_range(-60.0..0.0),
+                lookahead: rng.gen_range(0.0..0.2),
+            },
+            FlockBird,
+            RunEntity,
+        ));
+    }
+}
+
+fn spawn_flock_label(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+    hud: Query<Entity, With<HudRoot>>,
+) {
+    if config.flock_size == 0 {
+        return;
+    }
+    commands
+        .spawn((
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 30.0,
+                    color: Color::DARK_GRAY,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(10.),
+                    bottom: Val::Px(10.),
+                    ..default()
+                },
+                ..default()
+            }),
+            FlockLabel,
+        ))
+        .set_parent(hud.single());
+}
+
+fn steer_flock(
+    config: Res<GameConfig>,
+    next: Res<NextPipe>,
+    pipes: Query<(&Transform, &Pipe)>,
+    mut birds: Query<(&Transform, &mut Movable, &Autopilot), With<FlockBird>>,
+) {
+    let next = next.0.and_then(|entity| pipes.get(entity).ok());
+    for (transform, mut movable, autopilot) in &mut birds {
+        let gap_y = next.map_or(0.0, |(pipe_transform, pipe)| {
+            pipe.nearest_opening(pipe_transform.translation.y, transform.translation.y)
+        });
+        if autopilot.wants_flap(transform.translation.y, movable.velocity.y, gap_y) {
+            flap(&mut movable, None, config.flap_speed);
+        }
+    }
+}
+
+fn kill_flock_birds(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    images: Res<Assets<Image>>,
+    colliders: Colliders,
+    birds: Query<(Entity, &GlobalTransform, &Handle<Image>), With<FlockBird>>,
+) {
+    let bounds = visible_bounds(&config);
+    for (entity, transform, sprite) in &birds {
+        let Some(image) = images.get(sprite) else {
+            continue;
+        };
+        let position = transform.translation();
+        if !(bounds.min.y..=bounds.max.y).contains(&position.y)
+            || find_collision(position, image.size(), &colliders, &images, &config).is_some()
+        {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+fn update_flock_label(
+    run_time: Res<RunTime>,
+    config: Res<GameConfig>,
+    birds: Query<(), With<FlockBird>>,
+    mut label: Query<&mut Text, With<FlockLabel>>,
+) {
+    let Ok(mut text) = label.get_single_mut() else {
+        return;
+    };
+    text.sections[0].value = format!(
+        "Flock: {}/{} alive after {:.0}s",
+        birds.iter().count(),
+        config.flock_size,
+        run_time.0,
+    );
+}
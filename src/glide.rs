@@ -0,0 +1,110 @@
+use bevy::prelude::*;
+
+use crate::{
+    autopilot::AutopilotControl, config::KeyBindings, save, FlapEvent, GameConfig, GameState,
+    Hovering, Player, Score,
+};
+
+const GLIDE_RECORD_PATH: &str = "glide_record.txt";
+
+/// Pipes passed in a row without flapping. The streak starts at zero each run and goes back to
+/// zero on every flap, including a held flap key. Pipes passed while hovering before the first
+/// flap don't count, since the bird isn't falling yet.
+#[derive(Resource)]
+pub struct GlideStreak {
+    current: u32,
+    run_best: u32,
+    lifetime_best: u32,
+    // The lifetime best changed this run and hasn't been saved yet
+    unsaved: bool,
+    // Score as of the last update, to spot pipes being passed
+    last_score: u32,
+}
+
+impl GlideStreak {
+    fn load() -> Self {
+        let lifetime_best = std::fs::read_to_string(GLIDE_RECORD_PATH)
+            .ok()
+            .and_then(|contents| contents.trim().parse().ok())
+            .unwrap_or_default();
+        Self {
+            current: 0,
+            run_best: 0,
+            lifetime_best,
+            unsaved: false,
+            last_score: 0,
+        }
+    }
+
+    /// Longest streak of the current or most recent run.
+    pub fn run_best(&self) -> u32 {
+        self.run_best
+    }
+
+    pub fn lifetime_best(&self) -> u32 {
+        self.lifetime_best
+    }
+}
+
+/// Tracks the longest flap-free streak of pipes, per run and across runs.
+pub struct GlidePlugin;
+
+impl Plugin for GlidePlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(GlideStreak::load())
+            .add_system(reset_streak.in_schedule(OnEnter(GameState::Playing)))
+            .add_system(track_streak.in_set(OnUpdate(GameState::Playing)))
+            .add_system(save_record.in_schedule(OnEnter(GameState::GameOver)))
+            .add_system(save_record.in_schedule(OnEnter(GameState::Victory)));
+    }
+}
+
+fn reset_streak(mut streak: ResMut<GlideStreak>) {
+    streak.current = 0;
+    streak.run_best = 0;
+    streak.last_score = 0;
+}
+
+fn track_streak(
+    config: Res<GameConfig>,
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    score: Res<Score>,
+    mut flaps: EventReader<FlapEvent>,
+    player: Query<Option<&Hovering>, With<Player>>,
+    autopilot: Res<AutopilotControl>,
+    mut streak: ResMut<GlideStreak>,
+) {
+    // Holding the key keeps flapping without sending more events
+    if flaps.iter().count() > 0 || keyboard_input.pressed(bindings.flap) {
+        streak.current = 0;
+    }
+    let passed = score.0.saturating_sub(streak.last_score);
+    streak.last_score = score.0;
+    let hovering = player
+        .get_single()
+        .map_or(true, |hovering| hovering.is_some());
+    if passed == 0 || hovering || !config.spawn_pipes || autopilot.assisted() {
+        return;
+    }
+
+    streak.current += passed;
+    streak.run_best = streak.run_best.max(streak.current);
+    if streak.current > streak.lifetime_best {
+        streak.lifetime_best = streak.current;
+        streak.unsaved = true;
+    }
+}
+
+fn save_record(mut streak: ResMut<GlideStreak>) {
+    if !streak.unsaved {
+        return;
+    }
+    streak.unsaved = false;
+    if let Err(err) = save::write_atomic(GLIDE_RECORD_PATH, streak.lifetime_best.to_string()) {
+        error!(
+            "Failed to save glide record to {}: {}",
+            GLIDE_RECORD_PATH, err
+        );
+    }
+}
@@ -0,0 +1,64 @@
+use bevy::{
+    asset::FileAssetIo, prelude::*, render::render_resource::TextureFormat, window::PrimaryWindow,
+    winit::WinitWindows,
+};
+use winit::window::Icon;
+
+const ICON_PATH: &str = "icon.png";
+
+#[derive(Resource)]
+struct WindowIcon(Handle<Image>);
+
+/// Sets the window icon from `assets/icon.png`, if one is shipped. Bevy has no icon
+/// setting of its own, so it's handed to winit once the image has loaded.
+pub struct IconPlugin;
+
+impl Plugin for IconPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(load_icon)
+            .add_system(set_icon.run_if(resource_exists::<WindowIcon>()));
+    }
+}
+
+fn load_icon(mut commands: Commands, asset_server: Res<AssetServer>) {
+    if !FileAssetIo::get_base_path()
+        .join("assets")
+        .join(ICON_PATH)
+        .exists()
+    {
+        info!("No icon at {}, keeping the default one", ICON_PATH);
+        return;
+    }
+    commands.insert_resource(WindowIcon(asset_server.load(ICON_PATH)));
+}
+
+fn set_icon(
+    mut commands: Commands,
+    icon: Res<WindowIcon>,
+    images: Res<Assets<Image>>,
+    windows: NonSend<WinitWindows>,
+    primary: Query<Entity, With<PrimaryWindow>>,
+) {
+    let Some(image) = images.get(&icon.0) else {
+        return;
+    };
+    // Only try once, whatever happens
+    commands.remove_resource::<WindowIcon>();
+
+    let Some(window) = primary
+        .get_single()
+        .ok()
+        .and_then(|entity| windows.get_window(entity))
+    else {
+        return;
+    };
+    let Some(rgba) = image.convert(TextureFormat::Rgba8UnormSrgb) else {
+        warn!("Unsupported pixel format in {}", ICON_PATH);
+        return;
+    };
+    let size = image.size();
+    match Icon::from_rgba(rgba.data, size.x as u32, size.y as u32) {
+        Ok(icon) => window.set_window_icon(Some(icon)),
+        Err(err) => warn!("Invalid icon {}: {}", ICON_PATH, err),
+    }
+}
@@ -0,0 +1,154 @@
+use std::collections::VecDeque;
+
+use bevy::prelude::*;
+
+use crate::{
+    apply_velocity, GameConfig, GameOverScreen, GameState, KeyBindings, Pipe, Player, RunTime,
+};
+
+// Gameplay seconds before a death that the replay shows
+const REPLAY_SECONDS: f32 = 2.0;
+// Bounds the buffer even at absurd frame rates
+const REPLAY_CAPACITY: usize = 1024;
+const REPLAY_SPEED: f32 = 0.35;
+
+struct ReplayFrame {
+    time: f32,
+    bird: Transform,
+    pipes: Vec<(Entity, Vec3)>,
+}
+
+/// The bird and pipes over the last couple of seconds of the run, oldest first. Played back in
+/// slow motion over the game-over screen when `GameConfig::instant_replay` is on.
+#[derive(Resource, Default)]
+pub struct InstantReplay {
+    frames: VecDeque<ReplayFrame>,
+    // Real seconds into the playback, while it runs
+    playback: Option<f32>,
+}
+
+impl InstantReplay {
+    pub fn playing(&self) -> bool {
+        self.playback.is_some()
+    }
+
+    fn push(&mut self, frame: ReplayFrame) {
+        while self.frames.len() >= REPLAY_CAPACITY
+            || self
+                .frames
+                .front()
+                .is_some_and(|oldest| oldest.time < frame.time - REPLAY_SECONDS)
+        {
+            self.frames.pop_front();
+        }
+        self.frames.push_back(frame);
+    }
+}
+
+/// Records every run and replays its end after a death. Restarting skips the replay first, so
+/// the same key that would restart only does so once the replay is over.
+pub struct InstantReplayPlugin;
+
+impl Plugin for InstantReplayPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InstantReplay>()
+            .add_system(clear.in_schedule(OnEnter(GameState::Playing)))
+            .add_system(
+                record
+                    .after(apply_velocity)
+                    .in_set(OnUpdate(GameState::Playing))
+                    .run_if(|config: Res<GameConfig>| config.instant_replay),
+            )
+            .add_system(start.in_schedule(OnEnter(GameState::GameOver)))
+            .add_system(play.in_set(OnUpdate(GameState::GameOver)));
+    }
+}
+
+fn clear(mut replay: ResMut<InstantReplay>) {
+    replay.frames.clear();
+    replay.playback = None;
+}
+
+fn record(
+    run_time: Res<RunTime>,
+    bird: Query<&Transform, With<Player>>,
+    pipes: Query<(Entity, &Transform), With<Pipe>>,
+    mut replay: ResMut<InstantReplay>,
+) {
+    let Ok(bird) = bird.get_single() else {
+        return;
+    };
+    replay.push(ReplayFrame {
+        time: run_time.0,
+        bird: *bird,
+        pipes: pipes
+            .iter()
+            .map(|(entity, transform)| (entity, transform.translation))
+            .collect(),
+    });
+}
+
+pub fn start(config: Res<GameConfig>, mut replay: ResMut<InstantReplay>) {
+    if config.instant_replay && !replay.frames.is_empty() {
+        replay.playback = Some(0.0);
+    }
+}
+
+// Moves the frozen run back through its last moments, then leaves it as it was at the death
+#[allow(clippy::too_many_arguments)]
+pub fn play(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut replay: ResMut<InstantReplay>,
+    mut bird: Query<&mut Transform, With<Player>>,
+    mut pipes: Query<&mut Transform, (With<Pipe>, Without<Player>)>,
+    mut screen: Query<&mut Visibility, With<GameOverScreen>>,
+) {
+    let Some(clock) = replay.playback else {
+        return;
+    };
+    // Reduce motion still shows the replay, only without the slow motion
+    let speed = if config.reduce_motion {
+        1.0
+    } else {
+        REPLAY_SPEED
+    };
+    let clock = clock + time.delta_seconds() * speed;
+    let (first, last) = match (replay.frames.front(), replay.frames.back()) {
+        (Some(first), Some(last)) => (first.time, last.time),
+        _ => return,
+    };
+    let skipped = keyboard_input.any_just_pressed([bindings.restart, bindings.quick_restart]);
+    let finished = skipped || first + clock >= last;
+
+    let frame = if finished {
+        replay.frames.back()
+    } else {
+        replay
+            .frames
+            .iter()
+            .take_while(|frame| frame.time <= first + clock)
+            .last()
+    };
+    if let Some(frame) = frame {
+        if let Ok(mut transform) = bird.get_single_mut() {
+            *transform = frame.bird;
+        }
+        for (entity, translation) in &frame.pipes {
+            if let Ok(mut transform) = pipes.get_mut(*entity) {
+                transform.translation = *translation;
+            }
+        }
+    }
+
+    for mut visibility in &mut screen {
+        *visibility = if finished {
+            Visibility::Inherited
+        } else {
+            Visibility::Hidden
+        };
+    }
+    replay.playback = (!finished).then_some(clock);
+}
@@ -0,0 +1,98 @@
+use std::{collections::HashMap, fmt::Display, path::PathBuf};
+
+use bevy::{asset::FileAssetIo, prelude::*};
+
+pub const ENGLISH: &str = "en";
+// Built in, so English is there even without the assets folder
+const ENGLISH_STRINGS: &str = include_str!("../assets/lang/en.ron");
+
+type Strings = HashMap<String, String>;
+
+/// UI strings in the player's language, from `assets/lang/<code>.ron`. Keys a language doesn't
+/// have fall back to English, and a language that fails to load is English throughout.
+#[derive(Resource)]
+pub struct Language {
+    code: String,
+    strings: Strings,
+    english: Strings,
+}
+
+impl Language {
+    pub fn load(code: &str) -> Self {
+        let english =
+            ron::from_str(ENGLISH_STRINGS).expect("The built-in English strings are invalid");
+        let strings = if code == ENGLISH {
+            Strings::new()
+        } else {
+            read(code).unwrap_or_else(|err| {
+                warn!("Using English, language {:?} failed to load: {}", code, err);
+                Strings::new()
+            })
+        };
+        Self {
+            code: code.to_string(),
+            strings,
+            english,
+        }
+    }
+
+    pub fn code(&self) -> &str {
+        &self.code
+    }
+
+    /// A key missing from English too shows up as itself, so it's easy to spot.
+    pub fn t<'a>(&'a self, key: &'a str) -> &'a str {
+        self.strings
+            .get(key)
+            .or_else(|| self.english.get(key))
+            .map_or(key, String::as_str)
+    }
+
+    /// `t` with `{0}`, `{1}` and so on replaced by `args`, so translations can reorder them.
+    pub fn format(&self, key: &str, args: &[&dyn Display]) -> String {
+        args.iter()
+            .enumerate()
+            .fold(self.t(key).to_string(), |text, (i, arg)| {
+                text.replace(&format!("{{{}}}", i), &arg.to_string())
+            })
+    }
+}
+
+fn dir() -> PathBuf {
+    FileAssetIo::get_base_path().join("assets").join("lang")
+}
+
+fn read(code: &str) -> Result<Strings, Box<dyn std::error::Error>> {
+    let contents = std::fs::read_to_string(dir().join(format!("{}.ron", code)))?;
+    Ok(ron::from_str(&contents)?)
+}
+
+/// Codes of every language in `assets/lang`, English always among them, in order.
+pub fn available() -> Vec<String> {
+    let mut codes: Vec<String> = std::fs::read_dir(dir())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "ron" {
+                return None;
+            }
+            Some(path.file_stem()?.to_str()?.to_string())
+        })
+        .chain([ENGLISH.to_string()])
+        .collect();
+    codes.sort();
+    codes.dedup();
+    codes
+}
+
+/// Fixed text that follows the language: the first section is set to the key's string
+/// whenever the language changes.
+#[derive(Component)]
+pub struct Localized(pub &'static str);
+
+pub fn relabel(language: Res<Language>, mut labels: Query<(&Localized, &mut Text)>) {
+    for (localized, mut text) in &mut labels {
+        text.sections[0].value = language.t(localized.0).to_string();
+    }
+}
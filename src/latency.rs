@@ -0,0 +1,112 @@
+use std::time::Instant;
+
+use bevy::{
+    diagnostic::{Diagnostic, DiagnosticId, Diagnostics},
+    input::InputSystem,
+    prelude::*,
+};
+
+use crate::{config::KeyBindings, jump, FlapEvent, GameConfig, HudRoot};
+
+const FLAP_LATENCY: DiagnosticId =
+    DiagnosticId::from_u128(0x5A1E_7C3B_0F4D_4E21_9B62_D08A_3C71_E4F5);
+// Flaps the rolling average covers
+const HISTORY: usize = 20;
+
+// When the flap key press came in, waiting for the flap it leads to
+#[derive(Resource, Default)]
+struct FlapPolled(Option<Instant>);
+
+#[derive(Component)]
+struct LatencyLabel;
+
+/// Measures the time between the flap key being polled and the flap being applied, logging each
+/// one and showing a rolling average. Only runs with `GameConfig::input_latency_diagnostics`.
+pub struct LatencyPlugin;
+
+impl Plugin for LatencyPlugin {
+    fn build(&self, app: &mut App) {
+        let enabled = |config: Res<GameConfig>| config.input_latency_diagnostics;
+        app.init_resource::<FlapPolled>()
+            .add_startup_system(setup.in_base_set(StartupSet::PostStartup))
+            .add_system(
+                note_poll
+                    .after(InputSystem)
+                    .in_base_set(CoreSet::PreUpdate)
+                    .run_if(enabled),
+            )
+            .add_system(measure.after(jump).run_if(enabled))
+            .add_system(update_label.after(measure).run_if(enabled));
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+    mut diagnostics: ResMut<Diagnostics>,
+    hud: Query<Entity, With<HudRoot>>,
+) {
+    diagnostics.add(Diagnostic::new(FLAP_LATENCY, "flap_latency", HISTORY).with_suffix("ms"));
+    if !config.input_latency_diagnostics {
+        return;
+    }
+    commands
+        .spawn((
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 24.0,
+                    color: Color::DARK_GRAY,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    right: Val::Px(10.),
+                    bottom: Val::Px(10.),
+                    ..default()
+                },
+                ..default()
+            }),
+            LatencyLabel,
+        ))
+        .set_parent(hud.single());
+}
+
+fn note_poll(
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut polled: ResMut<FlapPolled>,
+) {
+    if keyboard_input.just_pressed(bindings.flap) {
+        polled.0 = Some(Instant::now());
+    }
+}
+
+// A press eaten by the flap cooldown never flaps, so a stale poll is dropped at the next press
+fn measure(
+    mut flaps: EventReader<FlapEvent>,
+    mut polled: ResMut<FlapPolled>,
+    mut diagnostics: ResMut<Diagnostics>,
+) {
+    if flaps.iter().count() == 0 {
+        return;
+    }
+    let Some(polled) = polled.0.take() else {
+        return;
+    };
+    let latency = polled.elapsed().as_secs_f64() * 1000.0;
+    info!("Flap latency: {:.3} ms", latency);
+    diagnostics.add_measurement(FLAP_LATENCY, || latency);
+}
+
+fn update_label(diagnostics: Res<Diagnostics>, mut label: Query<&mut Text, With<LatencyLabel>>) {
+    let Ok(mut text) = label.get_single_mut() else {
+        return;
+    };
+    if let Some(average) = diagnostics.get(FLAP_LATENCY).and_then(|d| d.average()) {
+        text.sections[0].value = format!("Flap latency: {:.2} ms", average);
+    }
+}
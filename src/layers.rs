@@ -0,0 +1,26 @@
+//! Z depths of everything drawn in the world, from back to front.
+//!
+//! The 2D camera only renders z in `0.0..1000.0`, so every layer stays inside that range.
+//! Children are offset relative to their parent's layer.
+
+pub const BACKGROUND: f32 = 0.0;
+/// Covers the static background when the sky shader is in use.
+pub const SKY: f32 = 0.5;
+pub const GAP_GUIDE: f32 = 1.0;
+pub const PIPES: f32 = 2.0;
+/// Pipe body segments sit just behind the pipe start so its lip overlaps them.
+pub const PIPE_BODY_OFFSET: f32 = -0.1;
+/// Animated overlays sit just in front of the sprite they decorate.
+pub const ANIMATION_OFFSET: f32 = 0.05;
+/// Pipe caps go over the pipe start and any animation on it.
+pub const PIPE_CAP_OFFSET: f32 = 0.1;
+/// In front of the pipes, so they disappear into it.
+pub const GROUND: f32 = 3.0;
+pub const TRAJECTORY: f32 = 4.0;
+pub const AI_BIRDS: f32 = 5.0;
+/// Just behind the bird they puff out from.
+pub const FLAP_PARTICLES: f32 = 5.5;
+pub const PLAYER: f32 = 6.0;
+pub const DEATH_PARTICLES: f32 = 7.0;
+pub const GAP_TELEGRAPH: f32 = 8.0;
+pub const FATAL_HITBOXES: f32 = 9.0;
@@ -1,23 +1,161 @@
-use std::sync::OnceLock;
+use std::{ops::RangeInclusive, sync::OnceLock};
 
+mod achievements;
+mod animation;
+mod autopilot;
+#[cfg(feature = "debug")]
+mod benchmark;
+mod checkpoint;
+mod comeback;
+mod config;
+mod daily;
+mod event_log;
+#[cfg(feature = "debug")]
+mod flock;
+mod glide;
+mod icon;
+mod instant_replay;
+mod lang;
+#[cfg(feature = "debug")]
+mod latency;
+mod layers;
+mod music;
+mod particles;
+mod performance;
+mod preload;
+mod save;
+mod settings;
+mod share_code;
+#[cfg(feature = "sky_shader")]
+mod sky;
+mod speed_curve;
+mod stages;
+mod survival;
+mod theme;
+#[cfg(feature = "debug")]
+mod time_control;
+mod transition;
+mod tumble;
+#[cfg(feature = "debug")]
+mod tuning;
+
+use achievements::Achievements;
+use autopilot::AutopilotControl;
 use bevy::{
     prelude::*,
-    window::{WindowMode, WindowResolution},
+    render::camera::{ScalingMode, Viewport},
+    sprite::Anchor,
+    window::{PrimaryWindow, WindowMode, WindowResolution},
 };
-use rand::Rng;
+use comeback::Comeback;
+use config::{BirdHitbox, GameConfig, GameMode, KeyBindings, ScoreTrigger};
+use daily::DailyChallenge;
+use instant_replay::InstantReplay;
+use lang::{Language, Localized};
+use performance::PerformanceMode;
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use settings::Settings;
+use share_code::ShareCode;
+use speed_curve::{CurveInput, SpeedCurve};
+use theme::Theme;
 
 const UP_SPEED: f32 = 500.0;
 const GRAVITY: f32 = -2000.0;
 const ANGLE_AMPLITUDE: f32 = 0.8;
 const PIPE_WINDOW_SIZE: f32 = 250.0;
+const PIPE_START_HEIGHT: f32 = 192.0;
+const PIPE_WIDTH: f32 = 144.0;
+const PIPE_SEGMENT_HEIGHT: f32 = 96.0;
+// A tunnel's middle is one pipe body segment
+const TUNNEL_WALL: f32 = 96.0;
 const PIPE_START_SPEED: f32 = 100.0;
 const PIPE_MAX_SPEED: f32 = 1000.0;
 const PIPE_TIME_TO_MAX: f32 = 60.0;
 const PIPE_GAP: f32 = 500.0;
 const PIPE_COUNT: usize = 10;
+// Openings never come closer than this to the floor or the top of the view
+const GAP_EDGE_MARGIN: f32 = 40.0;
+const COLLISION_GRACE: f32 = 1.0;
+const SPEED_TIERS: u32 = 5;
+// The progress bar fills up once per this many pipes
+const MILESTONE_PIPES: u32 = 10;
+const ASSIST_TIME_SCALE: f32 = 0.7;
+// Slow-motion on a new best dips to this scale, then eases back over the duration
+const SLOW_MOTION_SCALE: f32 = 0.3;
+const SLOW_MOTION_DURATION: f32 = 1.5;
+const MIN_CAMERA_ZOOM: f32 = 0.5;
+const MAX_CAMERA_ZOOM: f32 = 2.0;
+// Keeps the HUD usable in tiny windows and on screens far bigger than anything tested
+const MIN_UI_SCALE: f32 = 0.5;
+const MAX_UI_SCALE: f32 = 4.0;
+const BACKGROUND_WIDTH: f32 = 1920.0;
+const GROUND_TILE_SIZE: f32 = 192.0;
+// The trajectory preview samples this many points, this many seconds apart
+const TRAJECTORY_DOTS: usize = 12;
+const TRAJECTORY_STEP: f32 = 0.05;
+// Ground assist flaps once the bird would hit the ground within this many seconds
+const GROUND_ASSIST_HORIZON: f32 = 0.25;
+const SQUASH_DURATION: f32 = 0.2;
+// Below this horizontal speed there's no direction to lean into
+const MIN_TILT_SPEED: f32 = 1.0;
+const GRAVITY_FLIP_CHANCE: f64 = 0.1;
+const FLASH_DURATION: f32 = 1.5;
+const FATAL_HITBOX_DURATION: f32 = 1.0;
+const HITBOX_OUTLINE_WIDTH: f32 = 2.0;
+const CIRCLE_OUTLINE_DOTS: usize = 24;
+// The ghost pipe is fully faded in once the real pipe is this close to the screen edge
+const GHOST_PIPE_FADE_DISTANCE: f32 = 400.0;
+const GHOST_PIPE_ALPHA: f32 = 0.3;
+// Camera lookahead at full pipe speed, as a fraction of the visible width
+const CAMERA_LOOKAHEAD: f32 = 0.08;
+// How quickly the camera eases toward its target offset, per second
+const CAMERA_EASING: f32 = 2.0;
+const HIGH_SCORE_PATH: &str = "high_score.txt";
+const ATTEMPTS_PATH: &str = "attempts.txt";
+const RUN_LOG_PATH: &str = "runs.csv";
+// Columns of the run log. Only ever add new ones at the end, so older files keep importing
+const RUN_LOG_HEADER: &str = "timestamp,score,duration,seed,ended_by";
 
 static WINDOW_SIZE: OnceLock<WindowResolution> = OnceLock::new();
 
+fn camera_zoom(config: &GameConfig) -> f32 {
+    config.camera_zoom.clamp(MIN_CAMERA_ZOOM, MAX_CAMERA_ZOOM)
+}
+
+// Size of the playfield the camera shows, in world units
+fn visible_size(config: &GameConfig) -> Vec2 {
+    let window = WINDOW_SIZE.get().unwrap();
+    let design = if config.letterbox {
+        Vec2::new(
+            window.height() * config.design_aspect_ratio,
+            window.height(),
+        )
+    } else {
+        Vec2::new(window.width(), window.height())
+    };
+    design / camera_zoom(config)
+}
+
+// The part of the world a `width` by `height` view shows, centred on the origin
+fn playfield_bounds(width: f32, height: f32) -> Rect {
+    Rect::from_center_size(Vec2::ZERO, Vec2::new(width, height))
+}
+
+// Pipes left of this in a view `width` wide are `buffer` out of sight and ready to be recycled
+fn left_border(width: f32, buffer: f32) -> f32 {
+    playfield_bounds(width, 0.0).min.x - buffer
+}
+
+// Pipes start `buffer` past the right edge of a view `width` wide, to scroll in rather than pop in
+fn right_border(width: f32, buffer: f32) -> f32 {
+    playfield_bounds(width, 0.0).max.x + buffer
+}
+
+fn visible_bounds(config: &GameConfig) -> Rect {
+    let size = visible_size(config);
+    playfield_bounds(size.x, size.y)
+}
+
 // type LoadCallback = Box<dyn Send + Sync + FnOnce(Vec<HandleUntyped>, &mut Commands)>;
 
 // struct LoadingBundle {
@@ -42,7 +180,7 @@ static WINDOW_SIZE: OnceLock<WindowResolution> = OnceLock::new();
 //     }
 // }
 
-#[derive(Component, Default)]
+#[derive(Component, Clone, Default)]
 struct Movable {
     velocity: Vec3,
     acceleration: Vec3,
@@ -51,12 +189,183 @@ struct Movable {
 #[derive(Component, Default)]
 struct Player;
 
+#[derive(Component, Clone, Default)]
+struct Pipe {
+    passed: bool,
+    // Height of the opening between the two halves, or of each of a tunnel's two openings
+    gap: f32,
+    // Height of the solid middle splitting a tunnel's opening in two, zero for a regular pipe
+    wall: f32,
+    // Index into `PipeLooks`
+    look: usize,
+}
+
+impl Pipe {
+    // Centres of the openings relative to the pipe's centre, one each side of a tunnel's middle
+    fn openings(&self) -> Vec<f32> {
+        if self.wall > 0.0 {
+            let offset = (self.wall + self.gap) / 2.0;
+            vec![-offset, offset]
+        } else {
+            vec![0.0]
+        }
+    }
+
+    // World y of each opening, for the pipe centred at `pipe_y`
+    fn world_openings(&self, pipe_y: f32) -> Vec<f32> {
+        self.openings()
+            .into_iter()
+            .map(|offset| pipe_y + offset)
+            .collect()
+    }
+
+    // World y of the opening nearest to `y`, for the pipe centred at `pipe_y`
+    fn nearest_opening(&self, pipe_y: f32, y: f32) -> f32 {
+        self.world_openings(pipe_y)
+            .into_iter()
+            .min_by(|a, b| (a - y).abs().partial_cmp(&(b - y).abs()).unwrap())
+            .unwrap()
+    }
+
+    // Distance between the two halves, tunnel middle included
+    fn opening_height(&self) -> f32 {
+        if self.wall > 0.0 {
+            2.0 * self.gap + self.wall
+        } else {
+            self.gap
+        }
+    }
+}
+
+// How a pipe can be drawn: the default sprites first, then every configured variant
+#[derive(Resource, Default)]
+struct PipeLooks(Vec<PipeLook>);
+
+#[derive(Clone)]
+struct PipeLook {
+    start: Handle<Image>,
+    segment: Handle<Image>,
+    // Resized to the default sprites, whatever the size of its own
+    resized: bool,
+}
+
+impl PipeLook {
+    fn size(&self, start: bool) -> Option<Vec2> {
+        let height = if start {
+            PIPE_START_HEIGHT
+        } else {
+            PIPE_SEGMENT_HEIGHT
+        };
+        self.resized.then_some(Vec2::new(PIPE_WIDTH, height))
+    }
+}
+
+// Middle of a tunnel. Every pipe has one, only collidable and visible while it is a tunnel
+#[derive(Component)]
+struct TunnelWall;
+
+#[derive(Component, Default)]
+struct RunEntity;
+
 #[derive(Component, Default)]
-struct Pipe;
+struct GameOverScreen;
+
+#[derive(Component)]
+struct RestartHoldFill;
 
 #[derive(Component, Default)]
 struct Collider;
 
+#[derive(Component, Default)]
+struct Background;
+
+#[derive(Component, Default)]
+struct Ground;
+
+#[derive(Component, Default)]
+struct GapGuide;
+
+#[derive(Component, Default)]
+struct GapTelegraph;
+
+#[derive(Component)]
+struct GhostPipe;
+
+// Either half of the ghost pipe, on the side of the gap given by the sign
+#[derive(Component)]
+struct GhostHalf(f32);
+
+// Dot number `n` shows where the bird will be `n + 1` steps from now
+#[derive(Component)]
+struct TrajectoryDot(usize);
+
+#[derive(Component, Default)]
+struct GravityFlipPickup;
+
+// Gravity and flaps are reversed while the timer runs
+#[derive(Component, Default)]
+struct GravityFlip(Timer);
+
+impl GravityFlip {
+    fn active(&self) -> bool {
+        !self.0.finished()
+    }
+
+    fn sign(&self) -> f32 {
+        if self.active() {
+            -1.0
+        } else {
+            1.0
+        }
+    }
+}
+
+// Counts down the squash-and-stretch started by the last flap
+#[derive(Component, Default)]
+struct Squash(Timer);
+
+// Counts down until the next flap is accepted. A press during the cooldown is buffered and
+// flaps, squash and all, as soon as it ends; a held flap key goes through then too
+#[derive(Component, Default)]
+struct FlapCooldown {
+    timer: Timer,
+    buffered: bool,
+}
+
+// Seconds of eased gravity left for the current flap's apex, refilled whenever the bird is
+// going up faster than the easing starts
+#[derive(Component, Default)]
+struct ApexHang(f32);
+
+// What's left of a smoothed flap: the push that stands in for gravity until the flap's speed
+// is reached
+#[derive(Component, Default)]
+struct FlapImpulse {
+    acceleration: Vec3,
+    remaining: f32,
+}
+
+// The bird holds still until the first flap switches gravity on, and the pipes wait with it so
+// hovering can't carry the bird into the first one
+#[derive(Component)]
+struct Hovering;
+
+// Run condition for the world's motion and clock, which start with the first flap
+fn run_started(hovering: Query<(), (With<Player>, With<Hovering>)>) -> bool {
+    hovering.is_empty()
+}
+
+// Run condition for everything that works on pipes, which Zen runs don't have
+fn spawns_pipes(config: Res<GameConfig>) -> bool {
+    config.spawn_pipes
+}
+
+// Marks the pipe piece that borders the gap; `away` points from the gap into the pipe
+#[derive(Component)]
+struct GapEdge {
+    away: Vec3,
+}
+
 #[derive(Bundle, Default)]
 struct PipeBundle {
     movable: Movable,
@@ -68,17 +377,250 @@ struct PipeBundle {
 struct PlayerBundle {
     movable: Movable,
     sprite: SpriteBundle,
+    squash: Squash,
+    flap_cooldown: FlapCooldown,
+    gravity_flip: GravityFlip,
+    apex_hang: ApexHang,
+    flap_impulse: FlapImpulse,
     marker: Player,
 }
 
 #[derive(Resource, Default)]
 struct Score(u32);
 
+// The next pipe the bird will encounter, shared by everything that needs to look ahead
+#[derive(Resource, Default)]
+pub(crate) struct NextPipe(pub(crate) Option<Entity>);
+
+// Every random decision about the pipe layout goes through here, so a seed reproduces a run
+#[derive(Resource)]
+struct PipeRng {
+    seed: u64,
+    // Replay the same seed on every restart instead of rolling a new one
+    fixed: bool,
+    rng: StdRng,
+    // Pipe looks draw from their own stream, so adding variants leaves layouts as they were
+    looks: StdRng,
+}
+
+impl PipeRng {
+    fn new(seed: u64, fixed: bool) -> Self {
+        Self {
+            seed,
+            fixed,
+            rng: StdRng::seed_from_u64(seed),
+            looks: StdRng::seed_from_u64(!seed),
+        }
+    }
+
+    fn restart(&mut self) {
+        if !self.fixed {
+            self.seed = rand::thread_rng().gen();
+        }
+        self.rng = StdRng::seed_from_u64(self.seed);
+        self.looks = StdRng::seed_from_u64(!self.seed);
+    }
+}
+
+fn pick_look(rng: &mut StdRng, looks: &PipeLooks) -> usize {
+    if looks.0.len() > 1 {
+        rng.gen_range(0..looks.0.len())
+    } else {
+        0
+    }
+}
+
+// Gameplay seconds since the run started, scaled like the rest of the simulation
+#[derive(Resource, Default)]
+struct RunTime(f32);
+
+// Multiplier on the time step of gameplay systems; input is read every frame regardless
+#[derive(Resource)]
+struct TimeScale(f32);
+
+impl Default for TimeScale {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+// The scale gameplay runs at outside of any slow-motion
+fn base_time_scale(config: &GameConfig) -> f32 {
+    if config.assist_mode {
+        ASSIST_TIME_SCALE
+    } else {
+        1.0
+    }
+}
+
+// Where the camera sits relative to the origin. Each effect keeps its own share, so they add up
+// instead of fighting over the transform
+#[derive(Resource, Default)]
+struct CameraOffset {
+    lookahead: f32,
+}
+
+impl CameraOffset {
+    fn total(&self) -> Vec2 {
+        Vec2::X * self.lookahead
+    }
+}
+
+// Counts down the celebratory slow-motion after beating the high score
+#[derive(Resource, Default)]
+struct SlowMotion(Timer);
+
+#[derive(Resource, Default)]
+struct HighScore {
+    value: u32,
+    // Set once the current run passes `value`, so "New best!" fires only once per run
+    beaten: bool,
+}
+
+impl HighScore {
+    fn load() -> Self {
+        let value = std::fs::read_to_string(HIGH_SCORE_PATH)
+            .ok()
+            .and_then(|contents| contents.trim().parse().ok())
+            .unwrap_or_default();
+        Self {
+            value,
+            beaten: false,
+        }
+    }
+}
+
+// Runs that have ended, this session and ever. Only the lifetime count is saved
+#[derive(Resource, Default)]
+struct Attempts {
+    session: u32,
+    lifetime: u32,
+}
+
+impl Attempts {
+    fn load() -> Self {
+        let lifetime = std::fs::read_to_string(ATTEMPTS_PATH)
+            .ok()
+            .and_then(|contents| contents.trim().parse().ok())
+            .unwrap_or_default();
+        Self {
+            session: 0,
+            lifetime,
+        }
+    }
+}
+
+#[derive(Component)]
+struct ScoreLabel;
+
+#[derive(Component)]
+struct HighScoreLabel;
+
+#[derive(Component)]
+struct StreamerLabel;
+
+#[derive(Component)]
+struct SpeedLabel;
+
+#[derive(Component)]
+struct ProgressBar;
+
+#[derive(Component)]
+struct ProgressFill;
+
+#[derive(Component)]
+struct HudRoot;
+
+// A message that pops up over the playfield for a moment, then hides itself
+#[derive(Component)]
+struct Flash(Timer);
+
+impl Flash {
+    fn bundle(message: &str, font: Handle<Font>, color: Color, top: Val) -> (TextBundle, Self) {
+        (
+            TextBundle {
+                visibility: Visibility::Hidden,
+                ..TextBundle::from_section(
+                    message,
+                    TextStyle {
+                        font,
+                        font_size: 60.0,
+                        color,
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    margin: UiRect {
+                        left: Val::Percent(45.),
+                        top,
+                        ..default()
+                    },
+                    ..default()
+                })
+            },
+            Self(Timer::from_seconds(FLASH_DURATION, TimerMode::Once)),
+        )
+    }
+}
+
+#[derive(Component)]
+struct NewBestLabel;
+
+#[derive(Component)]
+struct MercyLabel;
+
+#[derive(Component)]
+struct AttemptLabel;
+
+#[derive(Component)]
+struct SeedLabel;
+
+#[derive(Component)]
+struct DailyLabel;
+
+#[derive(Component)]
+struct PipeCap;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum CollisionKind {
+    Pipe,
+    Ground,
+}
+
+// Sent by collision detection for the player, read by `handle_death`. Carries both hitboxes
+// as they were tested, in world space
+struct CollisionEvent {
+    kind: CollisionKind,
+    bird: Rect,
+    collider: Rect,
+}
+
+// Outline of a hitbox that ended a run, removed after `FATAL_HITBOX_DURATION`
+#[derive(Component)]
+struct FatalHitbox(Timer);
+
+// A flap the player pressed for, as opposed to the repeats of a held key
+struct FlapEvent {
+    y: f32,
+}
+
+// What ended the last run, for the game-over screen
+#[derive(Resource, Default)]
+struct DeathCause(Option<CollisionKind>);
+
+// The first death of a session can be forgiven once
+#[derive(Resource, Default)]
+struct Mercy {
+    used: bool,
+}
+
 #[derive(Clone, Eq, PartialEq, Debug, Hash, Default, States)]
 enum GameState {
     #[default]
     Playing,
     GameOver,
+    // Reached `GameConfig::win_score`
+    Victory,
 }
 
 // fn post_loading(
@@ -103,124 +645,182 @@ enum GameState {
 //     }
 // }
 
-fn startup(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn(Camera2dBundle::default());
-    commands.spawn(PlayerBundle {
-        sprite: SpriteBundle {
-            texture: asset_server.load("sprites/bird.png"),
-            ..default()
-        },
-        movable: Movable {
-            acceleration: Vec3::Y * GRAVITY,
-            ..default()
-        },
-        ..default()
-    });
-
-    let pipe_start = asset_server.load("sprites/pipe.png");
-    let pipe_segment = asset_server.load("sprites/pipe_piece.png");
+fn startup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut theme: ResMut<Theme>,
+    config: Res<GameConfig>,
+    language: Res<Language>,
+    high_score: Res<HighScore>,
+    daily: Option<Res<DailyChallenge>>,
+) {
+    let mut camera = Camera2dBundle::default();
+    camera.projection.scale = 1.0 / camera_zoom(&config);
+    if config.letterbox {
+        // Always the same world area; `fit_viewport` keeps the viewport's shape matching it
+        let design = visible_size(&config) * camera_zoom(&config);
+        camera.projection.scaling_mode = ScalingMode::Fixed {
+            width: design.x,
+            height: design.y,
+        };
+    }
+    commands.spawn(camera);
 
-    let pipe_start_height = 192.0;
+    let background = theme.image(&asset_server, "sprites/background.png");
+    // Tiles leapfrog each other, with one spare so the view is always covered
+    let visible_width = visible_size(&config).x;
+    let first_tile = (BACKGROUND_WIDTH - visible_width) / 2.0;
+    for i in 0..tile_count(visible_width, BACKGROUND_WIDTH) {
+        commands.spawn((
+            SpriteBundle {
+                texture: background.clone(),
+                transform: Transform::from_xyz(
+                    first_tile + i as f32 * BACKGROUND_WIDTH,
+                    0.0,
+                    layers::BACKGROUND,
+                ),
+                ..default()
+            },
+            Background,
+        ));
+    }
 
-    let pipe_segment_height = 96.0;
+    let ground = theme.image(&asset_server, "sprites/floor.png");
+    let first_tile = (GROUND_TILE_SIZE - visible_width) / 2.0;
+    for i in 0..tile_count(visible_width, GROUND_TILE_SIZE) {
+        for y in ground_row_ys(&config) {
+            commands.spawn((
+                SpriteBundle {
+                    texture: ground.clone(),
+                    transform: Transform::from_xyz(
+                        first_tile + i as f32 * GROUND_TILE_SIZE,
+                        y,
+                        layers::GROUND,
+                    ),
+                    ..default()
+                },
+                Ground,
+            ));
+        }
+    }
 
-    let lower_pipe_bundle = SpriteBundle {
-        texture: pipe_start,
-        transform: Transform {
-            translation: Vec3::NEG_Y * (pipe_start_height + PIPE_WINDOW_SIZE) / 2.0,
+    // Marks the exact death line, to check it against the ground art
+    #[cfg(feature = "debug")]
+    commands.spawn(SpriteBundle {
+        sprite: Sprite {
+            color: Color::RED,
+            custom_size: Some(Vec2::new(visible_width, 2.0)),
             ..default()
         },
+        transform: Transform::from_xyz(0.0, floor_y(&config), layers::GROUND + 0.1),
         ..default()
-    };
+    });
 
-    let mut upper_pipe_bundle = lower_pipe_bundle.clone();
-    upper_pipe_bundle.sprite.flip_y = true;
-    upper_pipe_bundle.transform.translation *= -1.0;
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(1.0, 1.0, 1.0, 0.2),
+                // Sized to each pipe's own gap as it's shown, which tuning and stages can change
+                custom_size: Some(Vec2::new(PIPE_WIDTH, 0.0)),
+                ..default()
+            },
+            transform: Transform::from_xyz(0.0, 0.0, layers::GAP_GUIDE),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        GapGuide,
+    ));
 
-    let mut rng = rand::thread_rng();
+    // A square turned on its corner, half of it past the screen edge, reads as an arrow
+    commands.spawn((
+        SpriteBundle {
+            sprite: Sprite {
+                color: Color::rgba(1.0, 1.0, 1.0, 0.35),
+                custom_size: Some(Vec2::splat(40.0)),
+                ..default()
+            },
+            transform: Transform::from_xyz(
+                visible_bounds(&config).max.x,
+                0.0,
+                layers::GAP_TELEGRAPH,
+            )
+            .with_rotation(Quat::from_rotation_z(std::f32::consts::FRAC_PI_4)),
+            visibility: Visibility::Hidden,
+            ..default()
+        },
+        GapTelegraph,
+    ));
 
-    let mut spawn_pipe = |x: f32| {
-        commands
-            .spawn(PipeBundle {
-                movable: Movable {
-                    acceleration: Vec3::NEG_X * (PIPE_MAX_SPEED - PIPE_START_SPEED)
-                        / PIPE_TIME_TO_MAX,
-                    velocity: Vec3::NEG_X * PIPE_START_SPEED,
-                },
-                sprite: SpriteBundle {
-                    transform: Transform {
-                        translation: Vec3 {
-                            x,
-                            y: rng.gen(),
+    let pipe_start = theme.image(&asset_server, "sprites/pipe.png");
+    commands
+        .spawn((
+            SpatialBundle {
+                transform: Transform::from_xyz(
+                    visible_bounds(&config).max.x - PIPE_WIDTH / 2.0,
+                    0.0,
+                    layers::PIPES,
+                ),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            GhostPipe,
+        ))
+        .with_children(|parent| {
+            for side in [-1.0, 1.0] {
+                parent.spawn((
+                    SpriteBundle {
+                        texture: pipe_start.clone(),
+                        sprite: Sprite {
+                            flip_y: side > 0.0,
                             ..default()
                         },
                         ..default()
                     },
+                    GhostHalf(side),
+                ));
+            }
+        });
+
+    for i in 0..TRAJECTORY_DOTS {
+        commands.spawn((
+            SpriteBundle {
+                sprite: Sprite {
+                    color: Color::rgba(1.0, 1.0, 1.0, 0.6),
+                    custom_size: Some(Vec2::splat(8.0)),
                     ..default()
                 },
+                transform: Transform::from_xyz(0.0, 0.0, layers::TRAJECTORY),
+                visibility: Visibility::Hidden,
                 ..default()
-            })
-            .with_children(|parent| {
-                parent
-                    .spawn((lower_pipe_bundle.clone(), Collider))
-                    .with_children(|parent| {
-                        for i in 0..10 {
-                            parent.spawn((
-                                SpriteBundle {
-                                    texture: pipe_segment.clone(),
-                                    transform: Transform {
-                                        translation: Vec3::NEG_Y
-                                            * pipe_segment_height
-                                            * (1 + 2 * i) as f32
-                                            / 2.0,
-                                        ..default()
-                                    },
-                                    ..default()
-                                },
-                                Collider,
-                            ));
-                        }
-                    });
-                parent
-                    .spawn((upper_pipe_bundle.clone(), Collider))
-                    .with_children(|parent| {
-                        for i in 0..10 {
-                            parent.spawn((
-                                SpriteBundle {
-                                    texture: pipe_segment.clone(),
-                                    transform: Transform {
-                                        translation: Vec3::Y
-                                            * pipe_segment_height
-                                            * (1 + 2 * i) as f32
-                                            / 2.0,
-                                        ..default()
-                                    },
-                                    ..default()
-                                },
-                                Collider,
-                            ));
-                        }
-                    });
-            });
-    };
-    let right_border = WINDOW_SIZE.get().unwrap().width() / 2.0 + 100.0;
-    for i in 0..10 {
-        spawn_pipe(right_border + i as f32 * PIPE_GAP);
+            },
+            TrajectoryDot(i),
+        ));
     }
 
     let font = asset_server.load("fonts/FiraSans-Bold.ttf");
-    commands.spawn(
-        TextBundle::from_sections([
-            TextSection::new(
-                "Score: ",
-                TextStyle {
-                    font: font.clone(),
-                    font_size: 50.0,
-                    color: Color::BLACK,
+    // Everything hidden by the HUD toggle hangs off this node
+    let hud = commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                    position_type: PositionType::Absolute,
+                    ..default()
                 },
-            ),
+                ..default()
+            },
+            HudRoot,
+        ))
+        .id();
+    let mut score_label = commands.spawn((
+        TextBundle::from_sections([
             TextSection::from_style(TextStyle {
-                font,
+                font: font.clone(),
+                font_size: 50.0,
+                color: Color::BLACK,
+            }),
+            TextSection::from_style(TextStyle {
+                font: font.clone(),
                 font_size: 50.0,
                 color: Color::BLACK,
             }),
@@ -233,119 +833,2436 @@ fn startup(mut commands: Commands, asset_server: Res<AssetServer>) {
             },
             ..default()
         }),
-    );
-}
+        ScoreLabel,
+        Localized("hud.score"),
+    ));
+    score_label.set_parent(hud);
 
-fn jump(keyboard_input: Res<Input<KeyCode>>, mut query: Query<&mut Movable, With<Player>>) {
-    if keyboard_input.pressed(KeyCode::Space) {
-        let mut movable = query.single_mut();
-        movable.velocity = Vec3::Y * UP_SPEED;
-    }
-}
+    // Streamer mode swaps the score for a bigger one in the corner
+    if config.streamer_mode {
+        score_label.insert(Visibility::Hidden);
+        commands
+            .spawn((
+                TextBundle::from_sections([
+                    TextSection::new(
+                        "0",
+                        TextStyle {
+                            font: font.clone(),
+                            font_size: 160.0,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    TextSection::from_style(TextStyle {
+                        font: font.clone(),
+                        font_size: 30.0,
+                        color: Color::GOLD,
+                    }),
+                ])
+                .with_text_alignment(TextAlignment::Right)
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        right: Val::Px(20.),
+                        top: Val::Px(10.),
+                        ..default()
+                    },
+                    ..default()
+                }),
+                StreamerLabel,
+            ))
+            .set_parent(hud);
+    }
+
+    commands
+        .spawn((
+            TextBundle::from_sections([
+                TextSection::from_style(TextStyle {
+                    font: font.clone(),
+                    font_size: 30.0,
+                    color: Color::DARK_GRAY,
+                }),
+                TextSection::new(
+                    high_score.value.to_string(),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 30.0,
+                        color: Color::DARK_GRAY,
+                    },
+                ),
+            ])
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                margin: UiRect {
+                    left: Val::Percent(50.),
+                    top: Val::Px(55.),
+                    ..default()
+                },
+                ..default()
+            }),
+            HighScoreLabel,
+            Localized("hud.best"),
+        ))
+        .set_parent(hud);
+
+    // The date and the day's best are filled in by `set_daily_label`
+    if daily.is_some() {
+        let style = TextStyle {
+            font: font.clone(),
+            font_size: 30.0,
+            color: Color::DARK_GRAY,
+        };
+        commands
+            .spawn((
+                TextBundle::from_sections([
+                    TextSection::new(language.t("hud.daily"), style.clone()),
+                    TextSection::new("", style),
+                ])
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(10.),
+                        top: Val::Px(10.),
+                        ..default()
+                    },
+                    ..default()
+                }),
+                DailyLabel,
+                Localized("hud.daily"),
+            ))
+            .set_parent(hud);
+    }
+
+    if config.assist_mode {
+        commands
+            .spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 30.0,
+                        color: Color::DARK_GRAY,
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(10.),
+                        bottom: Val::Px(10.),
+                        ..default()
+                    },
+                    ..default()
+                }),
+                Localized("hud.assist_mode"),
+            ))
+            .set_parent(hud);
+    }
+
+    if config.ground_assist {
+        commands
+            .spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 30.0,
+                        color: Color::DARK_GRAY,
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(10.),
+                        bottom: Val::Px(45.),
+                        ..default()
+                    },
+                    ..default()
+                }),
+                Localized("hud.ground_assist"),
+            ))
+            .set_parent(hud);
+    }
+
+    commands
+        .spawn((
+            TextBundle {
+                visibility: if config.hud_minimal {
+                    Visibility::Hidden
+                } else {
+                    Visibility::Inherited
+                },
+                ..TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 30.0,
+                        color: Color::DARK_GRAY,
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        right: Val::Px(10.),
+                        top: Val::Px(10.),
+                        ..default()
+                    },
+                    ..default()
+                })
+            },
+            SpeedLabel,
+        ))
+        .set_parent(hud);
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Percent(50.),
+                        top: Val::Px(100.),
+                        ..default()
+                    },
+                    size: Size::new(Val::Px(200.), Val::Px(8.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.25).into(),
+                visibility: Visibility::Hidden,
+                ..default()
+            },
+            ProgressBar,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(0.), Val::Percent(100.)),
+                        ..default()
+                    },
+                    background_color: Color::GOLD.into(),
+                    ..default()
+                },
+                ProgressFill,
+            ));
+        })
+        .set_parent(hud);
+
+    // Shares the corner with the daily challenge label, which never shows at the same time
+    commands
+        .spawn((
+            TextBundle {
+                visibility: Visibility::Hidden,
+                ..TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 30.0,
+                        color: Color::DARK_GRAY,
+                    },
+                )
+                .with_style(Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Px(10.),
+                        top: Val::Px(10.),
+                        ..default()
+                    },
+                    ..default()
+                })
+            },
+            SeedLabel,
+        ))
+        .set_parent(hud);
+
+    commands
+        .spawn((
+            Flash::bundle("", font.clone(), Color::GOLD, Val::Percent(20.)),
+            NewBestLabel,
+            Localized("hud.new_best"),
+        ))
+        .set_parent(hud);
+    commands
+        .spawn((
+            Flash::bundle("", font.clone(), Color::ORANGE_RED, Val::Percent(30.)),
+            MercyLabel,
+            Localized("hud.mercy"),
+        ))
+        .set_parent(hud);
+    commands
+        .spawn((
+            Flash::bundle("", font.clone(), Color::WHITE, Val::Percent(10.)),
+            AttemptLabel,
+        ))
+        .set_parent(hud);
+    // Smaller and further left than the other flashes, the prompt is a whole sentence
+    let (mut reset_prompt, flash) = Flash::bundle("", font, Color::WHITE, Val::Percent(45.));
+    reset_prompt.text.sections[0].style.font_size = 30.0;
+    reset_prompt.style.margin.left = Val::Percent(35.);
+    commands
+        .spawn((reset_prompt, flash, settings::SettingsResetLabel))
+        .set_parent(hud);
+}
+
+// Everything belonging to a single run, despawned on restart
+fn spawn_run(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut theme: ResMut<Theme>,
+    config: Res<GameConfig>,
+    comeback: Res<Comeback>,
+    mut pipe_rng: ResMut<PipeRng>,
+    mut atlases: ResMut<Assets<TextureAtlas>>,
+) {
+    let mut player = commands.spawn((
+        PlayerBundle {
+            sprite: SpriteBundle {
+                texture: theme.image(&asset_server, "sprites/bird.png"),
+                transform: Transform::from_xyz(0.0, 0.0, layers::PLAYER),
+                ..default()
+            },
+            movable: Movable {
+                acceleration: if config.gravity_from_spawn {
+                    Vec3::Y * config.gravity
+                } else {
+                    Vec3::ZERO
+                },
+                ..default()
+            },
+            ..default()
+        },
+        RunEntity,
+    ));
+    if !config.gravity_from_spawn {
+        player.insert(Hovering);
+    }
+
+    if !config.spawn_pipes {
+        return;
+    }
+
+    let pipe_start = theme.image(&asset_server, "sprites/pipe.png");
+    let pipe_segment = theme.image(&asset_server, "sprites/pipe_piece.png");
+    let pipe_cap = config
+        .pipe_caps
+        .then(|| theme.optional_image(&asset_server, "sprites/pipe_cap.png"))
+        .flatten();
+    let mut looks = vec![PipeLook {
+        start: pipe_start.clone(),
+        segment: pipe_segment.clone(),
+        resized: false,
+    }];
+    looks.extend(config.pipe_variants.iter().filter_map(|name| {
+        Some(PipeLook {
+            start: theme.optional_image(&asset_server, &format!("sprites/pipe_{}.png", name))?,
+            segment: theme
+                .optional_image(&asset_server, &format!("sprites/pipe_piece_{}.png", name))?,
+            resized: true,
+        })
+    }));
+    let looks = PipeLooks(looks);
+
+    // One atlas shared by every pipe start
+    let pipe_start_animation = config.pipe_start_animation.as_ref().map(|animation| {
+        (
+            animation,
+            animation::load_atlas(animation, &asset_server, &mut atlases),
+        )
+    });
+
+    let gap = config.gap_size + comeback.gap_bonus(&config);
+    let speed_factor = comeback.speed_factor(&config);
+    let lower_pipe_bundle = SpriteBundle {
+        texture: pipe_start,
+        transform: Transform {
+            translation: Vec3::NEG_Y * (PIPE_START_HEIGHT + gap) / 2.0,
+            ..default()
+        },
+        ..default()
+    };
+
+    let mut upper_pipe_bundle = lower_pipe_bundle.clone();
+    upper_pipe_bundle.sprite.flip_y = true;
+    upper_pipe_bundle.transform.translation *= -1.0;
+
+    let PipeRng {
+        rng,
+        looks: look_rng,
+        ..
+    } = &mut *pipe_rng;
+
+    let mut x = right_border(visible_size(&config).x, config.offscreen_buffer);
+    let positions: Vec<f32> = (0..PIPE_COUNT)
+        .map(|_| {
+            let position = x;
+            x += pipe_spacing(&config, rng);
+            position
+        })
+        .collect();
+
+    let pickup = theme.image(&asset_server, "sprites/smoke.png");
+
+    let mut previous: Option<(f32, f32)> = None;
+    let mut spawn_pipe = |x: f32, first: bool| {
+        let has_pickup = config.gravity_flip_powerup && rng.gen_bool(GRAVITY_FLIP_CHANCE);
+        // Drawn either way, so the option doesn't shift the rest of a seeded layout
+        let gap_y = rng.gen_range(gap_y_range(&config, gap));
+        let gap_y = match previous {
+            _ if first && config.center_first_pipe => 0.0,
+            Some((previous_x, previous_y)) => {
+                reachable_gap_y(&config, previous_y, gap_y, x - previous_x, PIPE_START_SPEED)
+            }
+            None => gap_y,
+        };
+        previous = Some((x, gap_y));
+        commands
+            .spawn((
+                PipeBundle {
+                    movable: Movable {
+                        acceleration: pipe_acceleration(&config) * speed_factor,
+                        velocity: Vec3::NEG_X * PIPE_START_SPEED * speed_factor,
+                    },
+                    sprite: SpriteBundle {
+                        transform: Transform {
+                            translation: Vec3 {
+                                x,
+                                y: gap_y,
+                                z: layers::PIPES,
+                            },
+                            ..default()
+                        },
+                        ..default()
+                    },
+                    marker: Pipe {
+                        gap,
+                        look: pick_look(look_rng, &looks),
+                        ..default()
+                    },
+                },
+                RunEntity,
+            ))
+            .with_children(|parent| {
+                parent.spawn((
+                    SpriteBundle {
+                        texture: pickup.clone(),
+                        sprite: Sprite {
+                            color: Color::PURPLE,
+                            ..default()
+                        },
+                        visibility: if has_pickup {
+                            Visibility::Inherited
+                        } else {
+                            Visibility::Hidden
+                        },
+                        ..default()
+                    },
+                    GravityFlipPickup,
+                ));
+                parent.spawn((
+                    SpriteBundle {
+                        texture: pipe_segment.clone(),
+                        visibility: Visibility::Hidden,
+                        ..default()
+                    },
+                    TunnelWall,
+                ));
+                parent
+                    .spawn((
+                        lower_pipe_bundle.clone(),
+                        Collider,
+                        GapEdge { away: Vec3::NEG_Y },
+                    ))
+                    .with_children(|parent| {
+                        if let Some((animation, atlas)) = &pipe_start_animation {
+                            parent.spawn(animation::overlay(animation, atlas.clone(), false));
+                        }
+                        if let Some(cap) = &pipe_cap {
+                            parent.spawn(pipe_cap_bundle(cap.clone(), false));
+                        }
+                        for i in 0..10 {
+                            parent.spawn((
+                                SpriteBundle {
+                                    texture: pipe_segment.clone(),
+                                    transform: Transform {
+                                        translation: Vec3::NEG_Y
+                                            * PIPE_SEGMENT_HEIGHT
+                                            * (1 + 2 * i) as f32
+                                            / 2.0
+                                            + Vec3::Z * layers::PIPE_BODY_OFFSET,
+                                        ..default()
+                                    },
+                                    ..default()
+                                },
+                                Collider,
+                            ));
+                        }
+                    });
+                parent
+                    .spawn((
+                        upper_pipe_bundle.clone(),
+                        Collider,
+                        GapEdge { away: Vec3::Y },
+                    ))
+                    .with_children(|parent| {
+                        if let Some((animation, atlas)) = &pipe_start_animation {
+                            parent.spawn(animation::overlay(animation, atlas.clone(), true));
+                        }
+                        if let Some(cap) = &pipe_cap {
+                            parent.spawn(pipe_cap_bundle(cap.clone(), true));
+                        }
+                        for i in 0..10 {
+                            parent.spawn((
+                                SpriteBundle {
+                                    texture: pipe_segment.clone(),
+                                    transform: Transform {
+                                        translation: Vec3::Y
+                                            * PIPE_SEGMENT_HEIGHT
+                                            * (1 + 2 * i) as f32
+                                            / 2.0
+                                            + Vec3::Z * layers::PIPE_BODY_OFFSET,
+                                        ..default()
+                                    },
+                                    ..default()
+                                },
+                                Collider,
+                            ));
+                        }
+                    });
+            });
+    };
+    for (i, x) in positions.into_iter().enumerate() {
+        spawn_pipe(x, i == 0);
+    }
+    commands.insert_resource(looks);
+}
+
+fn restart(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut held: Local<Option<f32>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut fill: Query<&mut Style, With<RestartHoldFill>>,
+) {
+    // There is no death animation yet, so quick restart only differs in its key
+    if keyboard_input.just_pressed(bindings.quick_restart)
+        || (config.restart_hold <= 0.0 && keyboard_input.just_pressed(bindings.restart))
+    {
+        game_state.set(GameState::Playing);
+        return;
+    }
+    if config.restart_hold <= 0.0 {
+        return;
+    }
+
+    // Only a press made on this screen counts, not a flap still held down from the run
+    *held = if keyboard_input.just_pressed(bindings.restart) {
+        Some(0.0)
+    } else if keyboard_input.pressed(bindings.restart) {
+        held.map(|seconds| seconds + time.delta_seconds())
+    } else {
+        None
+    };
+    let progress = held.map_or(0.0, |seconds| seconds / config.restart_hold);
+    if progress >= 1.0 {
+        *held = None;
+        game_state.set(GameState::Playing);
+    }
+
+    // Reduce motion fills it in quarters instead of sweeping
+    let shown = if config.reduce_motion {
+        (progress * 4.0).floor() / 4.0
+    } else {
+        progress
+    };
+    for mut style in &mut fill {
+        style.size.width = Val::Percent(shown.min(1.0) * 100.0);
+    }
+}
+
+// The instant replay shows the screen once it's over, so it isn't in the way for a frame first
+fn game_over_visibility(replay: &InstantReplay) -> Visibility {
+    if replay.playing() {
+        Visibility::Hidden
+    } else {
+        Visibility::Inherited
+    }
+}
+
+fn spawn_restart_hold(mut commands: Commands, config: Res<GameConfig>, replay: Res<InstantReplay>) {
+    if config.restart_hold <= 0.0 {
+        return;
+    }
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    position_type: PositionType::Absolute,
+                    position: UiRect {
+                        left: Val::Percent(50.),
+                        top: Val::Percent(30.),
+                        ..default()
+                    },
+                    margin: UiRect::left(Val::Px(-100.)),
+                    size: Size::new(Val::Px(200.), Val::Px(8.)),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.25).into(),
+                visibility: game_over_visibility(&replay),
+                ..default()
+            },
+            GameOverScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                NodeBundle {
+                    style: Style {
+                        size: Size::new(Val::Percent(0.), Val::Percent(100.)),
+                        ..default()
+                    },
+                    background_color: Color::WHITE.into(),
+                    ..default()
+                },
+                RestartHoldFill,
+            ));
+        });
+}
+
+// Hangs from the gap end of a pipe start into the pipe, and moves with the start as the gap
+// changes. It has no collider, so it's purely decoration
+fn pipe_cap_bundle(texture: Handle<Image>, upper: bool) -> (SpriteBundle, PipeCap) {
+    let (edge, anchor) = if upper {
+        (-1.0, Anchor::BottomCenter)
+    } else {
+        (1.0, Anchor::TopCenter)
+    };
+    (
+        SpriteBundle {
+            texture,
+            sprite: Sprite {
+                flip_y: upper,
+                anchor,
+                ..default()
+            },
+            transform: Transform::from_xyz(
+                0.0,
+                edge * PIPE_START_HEIGHT / 2.0,
+                layers::PIPE_CAP_OFFSET,
+            ),
+            ..default()
+        },
+        PipeCap,
+    )
+}
+
+fn reset_run(
+    mut commands: Commands,
+    run_entities: Query<Entity, Or<(With<RunEntity>, With<GameOverScreen>)>>,
+    mut score: ResMut<Score>,
+    mut high_score: ResMut<HighScore>,
+    mut run_time: ResMut<RunTime>,
+    mut pipe_rng: ResMut<PipeRng>,
+    mut slow_motion: ResMut<SlowMotion>,
+    mut next_pipe: ResMut<NextPipe>,
+) {
+    for entity in &run_entities {
+        commands.entity(entity).despawn_recursive();
+    }
+    // Nothing replaces it in runs without pipes
+    next_pipe.0 = None;
+    score.0 = 0;
+    high_score.beaten = false;
+    run_time.0 = 0.0;
+    pipe_rng.restart();
+    // A run that ended mid-celebration shouldn't start the next one in slow-motion
+    *slow_motion = SlowMotion::default();
+}
+
+fn tick_run_time(time: Res<Time>, time_scale: Res<TimeScale>, mut run_time: ResMut<RunTime>) {
+    run_time.0 += time.delta_seconds() * time_scale.0;
+}
+
+#[allow(clippy::too_many_arguments)]
+fn jump(
+    mut commands: Commands,
+    mut flaps: EventWriter<FlapEvent>,
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    config: Res<GameConfig>,
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut query: Query<
+        (
+            Entity,
+            &Transform,
+            &mut Movable,
+            &mut Squash,
+            &mut FlapCooldown,
+            &GravityFlip,
+            &mut FlapImpulse,
+            Option<&Hovering>,
+        ),
+        With<Player>,
+    >,
+) {
+    let (
+        entity,
+        transform,
+        mut movable,
+        mut squash,
+        mut cooldown,
+        gravity_flip,
+        mut impulse,
+        hovering,
+    ) = query.single_mut();
+    cooldown.timer.tick(time.delta().mul_f32(time_scale.0));
+    let just_pressed = keyboard_input.just_pressed(bindings.flap);
+    if !cooldown.timer.finished() {
+        cooldown.buffered |= just_pressed;
+        return;
+    }
+    // Only a new press squashes and counts as a flap, not the key being held down
+    let new_press = just_pressed || std::mem::take(&mut cooldown.buffered);
+    if !new_press && !keyboard_input.pressed(bindings.flap) {
+        return;
+    }
+    if hovering.is_some() {
+        commands.entity(entity).remove::<Hovering>();
+        movable.acceleration = Vec3::Y * config.gravity;
+    }
+    flap_player(&config, &mut movable, gravity_flip, &mut impulse);
+    if new_press {
+        squash.0 = Timer::from_seconds(SQUASH_DURATION, TimerMode::Once);
+        flaps.send(FlapEvent {
+            y: transform.translation.y,
+        });
+    }
+    if config.flap_cooldown > 0.0 {
+        cooldown.timer = Timer::from_seconds(config.flap_cooldown, TimerMode::Once);
+    }
+}
+
+// Purely visual: a hovering bird has no velocity, and the first flap starts from wherever it is
+fn bob_hovering(
+    config: Res<GameConfig>,
+    run_time: Res<RunTime>,
+    mut query: Query<&mut Transform, (With<Player>, With<Hovering>)>,
+) {
+    if config.reduce_motion {
+        return;
+    }
+    let phase = run_time.0 * config.hover_bob_speed * std::f32::consts::TAU;
+    for mut transform in &mut query {
+        transform.translation.y = config.hover_bob_amplitude * phase.sin();
+    }
+}
+
+// The single flap impulse shared by the player and every AI-driven bird
+fn flap(movable: &mut Movable, gravity_flip: Option<&GravityFlip>, speed: f32) {
+    movable.velocity = Vec3::Y * speed * gravity_flip.map_or(1.0, GravityFlip::sign);
+}
+
+// The bird's own flap, smoothed with `GameConfig::flap_smoothing_time`. It ends at the same
+// speed as an instant one, only that much later
+fn flap_player(
+    config: &GameConfig,
+    movable: &mut Movable,
+    gravity_flip: &GravityFlip,
+    impulse: &mut FlapImpulse,
+) {
+    if config.flap_smoothing_time <= 0.0 {
+        impulse.remaining = 0.0;
+        flap(movable, Some(gravity_flip), config.flap_speed);
+        return;
+    }
+    let target = Vec3::Y * config.flap_speed * gravity_flip.sign();
+    let change = target - movable.velocity;
+    let smoothed = change * config.flap_smoothing.clamp(0.0, 1.0);
+    movable.velocity += change - smoothed;
+    impulse.acceleration = smoothed / config.flap_smoothing_time;
+    impulse.remaining = config.flap_smoothing_time;
+}
+
+// Only touches the scale: collisions use the image size, so the hitbox stays the same
+fn squash_and_stretch(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    performance: Res<PerformanceMode>,
+    mut query: Query<(&mut Transform, &mut Squash), With<Player>>,
+) {
+    for (mut transform, mut squash) in &mut query {
+        squash.0.tick(time.delta());
+        let stretch = if config.reduce_motion || performance.active {
+            0.0
+        } else {
+            config.squash_stretch * config.effects_intensity() * squash.0.percent_left()
+        };
+        transform.scale = Vec3::new(1.0 - stretch / 2.0, 1.0 + stretch, 1.0);
+    }
+}
+
+fn rotate(
+    config: Res<GameConfig>,
+    pipes: Query<&Movable, With<Pipe>>,
+    mut query: Query<(&mut Transform, &Movable), With<Player>>,
+) {
+    // The bird stays put while the world scrolls past, so its own velocity has no forward part
+    let scroll = pipes.iter().next().map_or(Vec3::ZERO, |pipe| pipe.velocity);
+    for (mut transform, movable) in &mut query {
+        use std::f32::consts::FRAC_PI_2;
+        let relative = movable.velocity - scroll;
+        let angle = if config.tilt_follows_velocity && relative.x.abs() > MIN_TILT_SPEED {
+            // The sprite always faces right, so leftward motion leans the same way
+            relative.y.atan2(relative.x.abs())
+        } else {
+            (movable.velocity.y / UP_SPEED) * ANGLE_AMPLITUDE
+        }
+        .clamp(-FRAC_PI_2, FRAC_PI_2);
+        transform.rotation = Quat::from_axis_angle(Vec3::Z, angle);
+    }
+}
+
+fn apply_acceleration(
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    config: Res<GameConfig>,
+    mut query: Query<(
+        &mut Movable,
+        Option<&GravityFlip>,
+        Option<&mut ApexHang>,
+        Option<&mut FlapImpulse>,
+    )>,
+) {
+    let dt = time.delta_seconds() * time_scale.0;
+    for (mut movable, gravity_flip, apex_hang, impulse) in &mut query {
+        // A smoothed flap pushes instead of gravity pulling, for whatever part of the step it
+        // still lasts
+        let mut dt = dt;
+        if let Some(mut impulse) = impulse.filter(|impulse| impulse.remaining > 0.0) {
+            let pushed = dt.min(impulse.remaining);
+            impulse.remaining -= pushed;
+            movable.velocity += impulse.acceleration * pushed;
+            dt -= pushed;
+        }
+        let sign = gravity_flip.map_or(1.0, GravityFlip::sign);
+        let mut acceleration = movable.acceleration;
+        acceleration.y *= sign;
+        if let Some(mut apex_hang) = apex_hang.filter(|_| config.apex_hang > 0.0) {
+            acceleration.y *= apex_gravity(&config, &mut apex_hang, movable.velocity.y * sign, dt);
+        }
+        movable.velocity = movable.velocity + acceleration * dt;
+    }
+}
+
+// Share of gravity applied this step, the least right at the apex. Only the acceleration
+// changes, so collisions are still tested against wherever the bird actually is
+fn apex_gravity(config: &GameConfig, apex_hang: &mut ApexHang, upward: f32, dt: f32) -> f32 {
+    let speed = config.apex_hang_speed.max(f32::EPSILON);
+    if upward > speed {
+        apex_hang.0 = config.apex_hang_time;
+        return 1.0;
+    }
+    if apex_hang.0 <= 0.0 || upward.abs() > speed {
+        return 1.0;
+    }
+    apex_hang.0 -= dt;
+    1.0 - config.apex_hang.clamp(0.0, 1.0) * (1.0 - upward.abs() / speed)
+}
+
+// Without pipes nothing ends the run, so the ground and the top of the screen stop the bird
+fn keep_in_view(
+    config: Res<GameConfig>,
+    images: Res<Assets<Image>>,
+    mut player: Query<(&mut Transform, &mut Movable, &Handle<Image>), With<Player>>,
+) {
+    for (mut transform, mut movable, sprite) in &mut player {
+        let half_height = images.get(sprite).map_or(0.0, |image| image.size().y / 2.0);
+        let bottom = floor_y(&config) + half_height;
+        let top = visible_bounds(&config).max.y - half_height;
+        if !(bottom..=top).contains(&transform.translation.y) {
+            transform.translation.y = transform.translation.y.clamp(bottom, top);
+            movable.velocity.y = 0.0;
+        }
+    }
+}
+
+fn collect_gravity_flip(
+    config: Res<GameConfig>,
+    images: Res<Assets<Image>>,
+    mut player: Query<(&GlobalTransform, &Handle<Image>, &mut GravityFlip), With<Player>>,
+    mut pickups: Query<
+        (&GlobalTransform, &Handle<Image>, &mut Visibility),
+        With<GravityFlipPickup>,
+    >,
+) {
+    use bevy::sprite::collide_aabb::*;
+    let (player_transform, player_sprite, mut gravity_flip) = player.single_mut();
+    let Some(player_image) = images.get(player_sprite) else {
+        return;
+    };
+
+    for (transform, sprite, mut visibility) in &mut pickups {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+        let Some(image) = images.get(sprite) else {
+            continue;
+        };
+        if collide(
+            player_transform.translation(),
+            player_image.size(),
+            transform.translation(),
+            image.size(),
+        )
+        .is_some()
+        {
+            *visibility = Visibility::Hidden;
+            gravity_flip.0 = Timer::from_seconds(config.gravity_flip_duration, TimerMode::Once);
+        }
+    }
+}
+
+fn tick_gravity_flip(
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    mut player: Query<(&mut GravityFlip, &mut Sprite), With<Player>>,
+) {
+    let delta = time.delta().mul_f32(time_scale.0);
+    for (mut gravity_flip, mut sprite) in &mut player {
+        gravity_flip.0.tick(delta);
+        sprite.flip_y = gravity_flip.active();
+    }
+}
+
+fn apply_velocity(
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    mut query: Query<(&Movable, &mut Transform)>,
+) {
+    let dt = time.delta_seconds() * time_scale.0;
+    for (movable, mut transform) in &mut query {
+        transform.translation += movable.velocity * dt;
+    }
+}
+
+fn scroll_background(
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    config: Res<GameConfig>,
+    camera_offset: Res<CameraOffset>,
+    pipes: Query<&Movable, With<Pipe>>,
+    mut last_camera_offset: Local<Vec2>,
+    mut backgrounds: Query<&mut Transform, With<Background>>,
+) {
+    // Following the camera part of the way makes up the rest of the parallax, so the background
+    // looks farther away than the world when the camera moves
+    let camera_delta = camera_offset.total() - *last_camera_offset;
+    *last_camera_offset = camera_offset.total();
+    let follow = camera_delta * (1.0 - config.background_parallax.clamp(0.0, 1.0));
+
+    let mut speed = config.background_speed;
+    if config.background_speed_follows_pipes {
+        if let Some(pipe) = pipes.iter().next() {
+            speed *= pipe.velocity.x.abs() / PIPE_START_SPEED;
+        }
+    }
+
+    let visible_width = visible_size(&config).x;
+    let left_edge = camera_offset.total().x + visible_bounds(&config).min.x;
+    let wrap_distance = tile_count(visible_width, BACKGROUND_WIDTH) as f32 * BACKGROUND_WIDTH;
+    let dt = time.delta_seconds() * time_scale.0;
+    for mut transform in &mut backgrounds {
+        transform.translation.x -= speed * dt;
+        transform.translation += follow.extend(0.0);
+        // Jump by whole tile widths only, so the seam never drifts
+        while transform.translation.x + BACKGROUND_WIDTH / 2.0 < left_edge {
+            transform.translation.x += wrap_distance;
+        }
+    }
+}
+
+// The ground moves with the world, unlike the parallax background
+fn scroll_ground(
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    config: Res<GameConfig>,
+    camera_offset: Res<CameraOffset>,
+    pipes: Query<&Movable, With<Pipe>>,
+    mut tiles: Query<&mut Transform, With<Ground>>,
+) {
+    let speed = pipes
+        .iter()
+        .next()
+        .map_or(PIPE_START_SPEED, |pipe| pipe.velocity.x.abs());
+
+    let visible_width = visible_size(&config).x;
+    let left_edge = camera_offset.total().x + visible_bounds(&config).min.x;
+    let wrap_distance = tile_count(visible_width, GROUND_TILE_SIZE) as f32 * GROUND_TILE_SIZE;
+    let dt = time.delta_seconds() * time_scale.0;
+    for mut transform in &mut tiles {
+        transform.translation.x -= speed * dt;
+        while transform.translation.x + GROUND_TILE_SIZE / 2.0 < left_edge {
+            transform.translation.x += wrap_distance;
+        }
+    }
+}
+
+// Tiles needed to cover the view, with one spare to leapfrog
+fn tile_count(visible_width: f32, tile_width: f32) -> usize {
+    (visible_width / tile_width).ceil() as usize + 1
+}
+
+// World y of the top of the ground, where the bird dies
+fn floor_y(config: &GameConfig) -> f32 {
+    visible_bounds(config).min.y + config.ground_height
+}
+
+// Centres of the rows of ground tiles, enough to reach from the floor line down past the bottom
+// of the screen
+fn ground_row_ys(config: &GameConfig) -> Vec<f32> {
+    let rows = (config.ground_height / GROUND_TILE_SIZE).ceil().max(1.0) as usize;
+    (0..rows)
+        .map(|row| floor_y(config) - (row as f32 + 0.5) * GROUND_TILE_SIZE)
+        .collect()
+}
+
+// Centres an opening `height` tall can be drawn at, clear of both the floor and the top of the
+// view. An opening too tall for that is centred between them
+fn gap_y_range(config: &GameConfig, height: f32) -> RangeInclusive<f32> {
+    let lowest = floor_y(config) + height / 2.0 + GAP_EDGE_MARGIN;
+    let highest = visible_bounds(config).max.y - height / 2.0 - GAP_EDGE_MARGIN;
+    if lowest > highest {
+        let middle = (lowest + highest) / 2.0;
+        middle..=middle
+    } else {
+        lowest..=highest
+    }
+}
+
+// Keeps a gap within reach of the previous one: `spacing` apart at `speed`, the bird can climb by
+// flapping nonstop or drop by falling freely, but no further
+fn reachable_gap_y(config: &GameConfig, previous_y: f32, y: f32, spacing: f32, speed: f32) -> f32 {
+    let t = spacing / speed;
+    let climb = config.flap_speed * t * config.gap_reach_margin;
+    let drop = -config.gravity * t * t / 2.0 * config.gap_reach_margin;
+    // Not `clamp`, which panics on what tuning can produce: a NaN reach from standing pipes and
+    // no flap, or bounds the wrong way round under upward gravity
+    y.max(previous_y - drop).min(previous_y + climb)
+}
+
+// Distance from one pipe to the next. Only touches the RNG when there's a range to draw from,
+// so classic spacing leaves seeded layouts as they were
+fn pipe_spacing(config: &GameConfig, rng: &mut StdRng) -> f32 {
+    let spacing = if config.pipe_spacing_max > config.pipe_spacing_min {
+        rng.gen_range(config.pipe_spacing_min..config.pipe_spacing_max)
+    } else {
+        config.pipe_spacing_min
+    };
+    spacing.max(PIPE_WIDTH * 2.0)
+}
+
+// Recycled pipes keep their `Movable`, so this only needs to be applied at spawn
+fn pipe_acceleration(config: &GameConfig) -> Vec3 {
+    // A speed curve sets the velocity directly instead
+    if config.pipe_acceleration_enabled && config.speed_curve.is_none() {
+        Vec3::NEG_X * (PIPE_MAX_SPEED - PIPE_START_SPEED) / PIPE_TIME_TO_MAX
+    } else {
+        Vec3::ZERO
+    }
+}
+
+// The gap recycled pipes get, tightening with the score when the hardcore modifier is on
+fn current_gap(config: &GameConfig, score: u32, bird_height: f32) -> f32 {
+    (config.gap_size - config.gap_shrink_per_point * score as f32)
+        .max(config.min_gap)
+        .min(config.gap_size)
+        .max(bird_height)
+}
+
+// Whichever opening of the previous pipe the bird took, every opening of the next one has to be
+// within reach
+fn openings_reachable(
+    config: &GameConfig,
+    previous: &[f32],
+    next: &[f32],
+    spacing: f32,
+    speed: f32,
+) -> bool {
+    previous.iter().all(|&from| {
+        next.iter()
+            .all(|&to| reachable_gap_y(config, from, to, spacing, speed) == to)
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn reuse_pipes(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    camera_offset: Res<CameraOffset>,
+    score: Res<Score>,
+    comeback: Res<Comeback>,
+    looks: Res<PipeLooks>,
+    images: Res<Assets<Image>>,
+    player: Query<&Handle<Image>, With<Player>>,
+    mut query: Query<(&mut Transform, &mut Pipe, &Movable, &Children)>,
+    mut pickups: Query<&mut Visibility, With<GravityFlipPickup>>,
+    mut walls: Query<(Entity, &mut Visibility), (With<TunnelWall>, Without<GravityFlipPickup>)>,
+    mut edges: Query<(&mut Transform, &GapEdge), Without<Pipe>>,
+    mut pipe_rng: ResMut<PipeRng>,
+) {
+    let bird_height = player
+        .get_single()
+        .ok()
+        .and_then(|sprite| images.get(sprite))
+        .map_or(0.0, |image| image.size().y);
+    let gap = current_gap(&config, score.0, bird_height) + comeback.gap_bonus(&config);
+    let border =
+        camera_offset.total().x + left_border(visible_size(&config).x, config.offscreen_buffer);
+    let Some((mut farther_position, mut farther_openings)) = query
+        .iter()
+        .map(|(x, pipe, _, _)| (x.translation, pipe.world_openings(x.translation.y)))
+        .max_by(|(t1, _), (t2, _)| t1.x.partial_cmp(&t2.x).unwrap())
+    else {
+        return;
+    };
+    let PipeRng {
+        rng,
+        looks: look_rng,
+        ..
+    } = &mut *pipe_rng;
+    for (mut transform, mut pipe, movable, children) in &mut query {
+        if transform.translation.x < border {
+            let spacing = pipe_spacing(&config, rng);
+            let speed = movable.velocity.x.abs();
+            let drawn_y = rng.gen_range(gap_y_range(&config, gap));
+            transform.translation = farther_position;
+            transform.translation.x += spacing;
+            transform.translation.y =
+                reachable_gap_y(&config, farther_position.y, drawn_y, spacing, speed);
+
+            // Only recycled pipes change, so the ones on screen stay as the player saw them.
+            // Only drawn with tunnels on, like the spacing, so classic seeds stay as they were
+            let look = pick_look(look_rng, &looks);
+            let tunnel = Pipe {
+                passed: false,
+                gap: config.tunnel_gap.max(bird_height),
+                wall: TUNNEL_WALL,
+                look,
+            };
+            let wants_tunnel =
+                config.tunnel_chance > 0.0 && rng.gen_bool(config.tunnel_chance.min(1.0));
+            // A tunnel is taller than a gap, so it may have to move to fit on screen
+            let tunnel_range = gap_y_range(&config, tunnel.opening_height());
+            let tunnel_y = transform
+                .translation
+                .y
+                .clamp(*tunnel_range.start(), *tunnel_range.end());
+            *pipe = if wants_tunnel
+                && openings_reachable(
+                    &config,
+                    &farther_openings,
+                    &tunnel.world_openings(tunnel_y),
+                    spacing,
+                    speed,
+                ) {
+                transform.translation.y = tunnel_y;
+                tunnel
+            } else {
+                Pipe {
+                    passed: false,
+                    gap,
+                    wall: 0.0,
+                    look,
+                }
+            };
+            farther_position = transform.translation;
+            farther_openings = pipe.world_openings(transform.translation.y);
+            shape_pipe(&mut commands, &pipe, children, &mut edges, &mut walls);
+
+            let has_pickup = config.gravity_flip_powerup && rng.gen_bool(GRAVITY_FLIP_CHANCE);
+            let mut pickups = pickups.iter_many_mut(children.iter());
+            while let Some(mut visibility) = pickups.fetch_next() {
+                *visibility = if has_pickup {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                };
+            }
+        }
+    }
+}
+
+// Moves a pipe's halves apart to fit its openings, and makes its tunnel middle solid or not
+fn shape_pipe(
+    commands: &mut Commands,
+    pipe: &Pipe,
+    children: &Children,
+    edges: &mut Query<(&mut Transform, &GapEdge), Without<Pipe>>,
+    walls: &mut Query<(Entity, &mut Visibility), (With<TunnelWall>, Without<GravityFlipPickup>)>,
+) {
+    let mut halves = edges.iter_many_mut(children.iter());
+    while let Some((mut half, edge)) = halves.fetch_next() {
+        half.translation.y = edge.away.y * (PIPE_START_HEIGHT + pipe.opening_height()) / 2.0;
+    }
+    let mut tunnel_walls = walls.iter_many_mut(children.iter());
+    while let Some((wall, mut visibility)) = tunnel_walls.fetch_next() {
+        if pipe.wall > 0.0 {
+            *visibility = Visibility::Inherited;
+            commands.entity(wall).insert(Collider);
+        } else {
+            *visibility = Visibility::Hidden;
+            commands.entity(wall).remove::<Collider>();
+        }
+    }
+}
+
+// The closest pipe that is not yet entirely behind `bird_x`
+pub(crate) fn next_pipe(
+    bird_x: f32,
+    pipes: impl IntoIterator<Item = (Entity, Vec3)>,
+) -> Option<(Entity, Vec3)> {
+    pipes
+        .into_iter()
+        .filter(|(_, position)| position.x + PIPE_WIDTH / 2.0 >= bird_x)
+        .min_by(|(_, p1), (_, p2)| p1.x.partial_cmp(&p2.x).unwrap())
+}
+
+fn update_next_pipe(
+    player: Query<&Transform, With<Player>>,
+    pipes: Query<(Entity, &Transform), With<Pipe>>,
+    mut next: ResMut<NextPipe>,
+) {
+    let found = next_pipe(
+        player.single().translation.x,
+        pipes
+            .iter()
+            .map(|(entity, transform)| (entity, transform.translation)),
+    )
+    .map(|(entity, _)| entity);
+    // Only write on change so readers can rely on change detection
+    if next.0 != found {
+        next.0 = found;
+    }
+}
+
+// A tunnel's guide picks the opening the bird is nearer to
+fn update_gap_guide(
+    config: Res<GameConfig>,
+    next: Res<NextPipe>,
+    player: Query<&Transform, With<Player>>,
+    pipes: Query<(&Transform, &Pipe)>,
+    mut guide: Query<
+        (&mut Transform, &mut Sprite, &mut Visibility),
+        (With<GapGuide>, Without<Pipe>, Without<Player>),
+    >,
+) {
+    let (mut guide_transform, mut sprite, mut visibility) = guide.single_mut();
+    let bird_y = player.get_single().map_or(0.0, |bird| bird.translation.y);
+    match next.0.and_then(|entity| pipes.get(entity).ok()) {
+        Some((transform, pipe)) if config.gap_guide => {
+            guide_transform.translation.x = transform.translation.x;
+            guide_transform.translation.y = pipe.nearest_opening(transform.translation.y, bird_y);
+            sprite.custom_size = Some(Vec2::new(PIPE_WIDTH, pipe.gap));
+            *visibility = Visibility::Visible;
+        }
+        _ => *visibility = Visibility::Hidden,
+    }
+}
+
+fn update_gap_telegraph(
+    config: Res<GameConfig>,
+    camera_offset: Res<CameraOffset>,
+    player: Query<&Transform, With<Player>>,
+    pipes: Query<(Entity, &Transform, &Pipe)>,
+    mut telegraph: Query<
+        (&mut Transform, &mut Visibility),
+        (With<GapTelegraph>, Without<Pipe>, Without<Player>),
+    >,
+) {
+    let (mut telegraph_transform, mut visibility) = telegraph.single_mut();
+    // next_pipe keeps pipes whose right side is past the given x, so shifting the edge by
+    // a pipe width leaves only pipes that haven't started entering the screen
+    let edge = camera_offset.total().x + visible_bounds(&config).max.x;
+    let upcoming = next_pipe(
+        edge + PIPE_WIDTH,
+        pipes
+            .iter()
+            .map(|(entity, transform, _)| (entity, transform.translation)),
+    );
+    let bird_y = player.get_single().map_or(0.0, |bird| bird.translation.y);
+    match upcoming.and_then(|(entity, _)| pipes.get(entity).ok()) {
+        Some((_, transform, pipe)) if config.gap_telegraph => {
+            telegraph_transform.translation.x = edge;
+            telegraph_transform.translation.y =
+                pipe.nearest_opening(transform.translation.y, bird_y);
+            *visibility = Visibility::Visible;
+        }
+        _ => *visibility = Visibility::Hidden,
+    }
+}
+
+fn update_ghost_pipe(
+    config: Res<GameConfig>,
+    camera_offset: Res<CameraOffset>,
+    pipes: Query<(Entity, &Transform, &Pipe)>,
+    mut ghost: Query<(&mut Transform, &mut Visibility), (With<GhostPipe>, Without<Pipe>)>,
+    mut halves: Query<
+        (&GhostHalf, &mut Transform, &mut Sprite),
+        (Without<GhostPipe>, Without<Pipe>),
+    >,
+) {
+    let (mut ghost_transform, mut visibility) = ghost.single_mut();
+    // Same upcoming pipe as the telegraph's, and recomputed every frame so recycling is no issue
+    let edge = camera_offset.total().x + visible_bounds(&config).max.x;
+    let upcoming = next_pipe(
+        edge + PIPE_WIDTH,
+        pipes
+            .iter()
+            .map(|(entity, transform, _)| (entity, transform.translation)),
+    );
+    let Some((entity, position)) = upcoming.filter(|_| config.ghost_pipe) else {
+        *visibility = Visibility::Hidden;
+        return;
+    };
+    // A tunnel's ghost shows only its outer halves
+    let gap = pipes
+        .get(entity)
+        .map_or(config.gap_size, |(_, _, pipe)| pipe.opening_height());
+    let distance = position.x - PIPE_WIDTH / 2.0 - edge;
+    let alpha = GHOST_PIPE_ALPHA * (1.0 - distance / GHOST_PIPE_FADE_DISTANCE).clamp(0.0, 1.0);
+
+    // Right where the pipe enters, so it replaces the ghost in place
+    ghost_transform.translation.x = edge - PIPE_WIDTH / 2.0;
+    ghost_transform.translation.y = position.y;
+    *visibility = Visibility::Visible;
+    for (half, mut transform, mut sprite) in &mut halves {
+        transform.translation.y = half.0 * (PIPE_START_HEIGHT + gap) / 2.0;
+        sprite.color.set_a(alpha);
+    }
+}
+
+// Height of a bird after `t` seconds without flapping
+fn predicted_y(y: f32, velocity: f32, acceleration: f32, t: f32) -> f32 {
+    y + velocity * t + acceleration * t * t / 2.0
+}
+
+// Flaps through the same impulse as the player's own whenever the bird's unflapped course hits
+// the ground within the horizon. A hovering bird isn't falling, so it's left alone
+fn ground_assist(
+    config: Res<GameConfig>,
+    images: Res<Assets<Image>>,
+    mut player: Query<
+        (
+            &Transform,
+            &Handle<Image>,
+            &mut Movable,
+            &GravityFlip,
+            &mut FlapImpulse,
+        ),
+        (With<Player>, Without<Hovering>),
+    >,
+) {
+    if !config.ground_assist {
+        return;
+    }
+    let Ok((transform, sprite, mut movable, gravity_flip, mut impulse)) = player.get_single_mut()
+    else {
+        return;
+    };
+    let half_height = images.get(sprite).map_or(0.0, |image| image.size().y / 2.0);
+    let acceleration = movable.acceleration.y * gravity_flip.sign();
+    let steps = (GROUND_ASSIST_HORIZON / TRAJECTORY_STEP).ceil() as usize;
+    let hits_ground = (0..=steps).any(|step| {
+        let t = step as f32 * TRAJECTORY_STEP;
+        predicted_y(transform.translation.y, movable.velocity.y, acceleration, t) - half_height
+            <= floor_y(&config)
+    });
+    if hits_ground {
+        flap_player(&config, &mut movable, gravity_flip, &mut impulse);
+    }
+}
+
+// Integrates the same motion as `apply_acceleration`, with the world scrolling by
+fn update_trajectory(
+    config: Res<GameConfig>,
+    player: Query<(&Transform, &Movable, &GravityFlip), With<Player>>,
+    pipes: Query<&Movable, (With<Pipe>, Without<Player>)>,
+    mut dots: Query<(&TrajectoryDot, &mut Transform, &mut Visibility), Without<Player>>,
+) {
+    let Ok((bird, movable, gravity_flip)) = player.get_single() else {
+        return;
+    };
+    let scroll = pipes
+        .iter()
+        .next()
+        .map_or(0.0, |pipe| pipe.velocity.x.abs());
+    let mut acceleration = movable.acceleration;
+    acceleration.y *= gravity_flip.sign();
+    let bottom = floor_y(&config);
+    let top = visible_bounds(&config).max.y;
+
+    let y_after = |step: usize| {
+        predicted_y(
+            bird.translation.y,
+            movable.velocity.y,
+            acceleration.y,
+            step as f32 * TRAJECTORY_STEP,
+        )
+    };
+
+    for (dot, mut transform, mut visibility) in &mut dots {
+        let t = (dot.0 + 1) as f32 * TRAJECTORY_STEP;
+        let y = y_after(dot.0 + 1);
+        // The arc ends where it first leaves the screen or meets the ground
+        let on_screen = (1..=dot.0 + 1).all(|step| (bottom..=top).contains(&y_after(step)));
+        *visibility = if config.trajectory_preview && on_screen {
+            Visibility::Visible
+        } else {
+            Visibility::Hidden
+        };
+        transform.translation.x = bird.translation.x + scroll * t;
+        transform.translation.y = y;
+    }
+}
+
+type Colliders<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static GlobalTransform,
+        &'static Handle<Image>,
+        &'static Sprite,
+        Option<&'static GapEdge>,
+    ),
+    With<Collider>,
+>;
+
+// Position and size of the hitbox actually tested for a collider
+fn collider_hitbox(
+    transform: &GlobalTransform,
+    mut size: Vec2,
+    gap_edge: Option<&GapEdge>,
+    config: &GameConfig,
+) -> (Vec3, Vec2) {
+    let mut position = transform.translation();
+    if let Some(gap_edge) = gap_edge {
+        // Shrink the piece from the sides and from the gap opening, keeping the far edge in place
+        let inset = config
+            .gap_edge_forgiveness
+            .clamp(0.0, size.min_element() / 2.0);
+        size -= Vec2::new(2.0 * inset, inset);
+        position += gap_edge.away * inset / 2.0;
+    }
+    (position, size)
+}
+
+// Bounding box of the bird's hitbox, for a bird sprite of `size` at `position`
+fn bird_bounds(config: &GameConfig, position: Vec2, size: Vec2) -> Rect {
+    match config.bird_hitbox {
+        BirdHitbox::Box => Rect::from_center_size(position, size),
+        BirdHitbox::Circle { radius } => {
+            Rect::from_center_size(position, Vec2::splat(2.0 * radius))
+        }
+    }
+}
+
+fn circle_overlaps_rect(center: Vec2, radius: f32, rect: Rect) -> bool {
+    // The point of the rectangle nearest the centre is the one that touches first
+    let nearest = center.clamp(rect.min, rect.max);
+    center.distance_squared(nearest) <= radius * radius
+}
+
+// Hitbox of the first collider overlapping a bird at `position`
+fn find_collision(
+    position: Vec3,
+    size: Vec2,
+    colliders: &Colliders,
+    images: &Assets<Image>,
+    config: &GameConfig,
+) -> Option<(Vec3, Vec2)> {
+    use bevy::sprite::collide_aabb::*;
+    colliders
+        .iter()
+        .find_map(|(transform, texture, sprite, gap_edge)| {
+            // Pipe variants are drawn at the default size, and hit at it too
+            let collider_size = match sprite.custom_size {
+                Some(size) => size,
+                None => images.get(texture)?.size(),
+            };
+            let hitbox = collider_hitbox(transform, collider_size, gap_edge, config);
+            let hit = match config.bird_hitbox {
+                BirdHitbox::Box => collide(position, size, hitbox.0, hitbox.1).is_some(),
+                BirdHitbox::Circle { radius } => circle_overlaps_rect(
+                    position.truncate(),
+                    radius,
+                    Rect::from_center_size(hitbox.0.truncate(), hitbox.1),
+                ),
+            };
+            hit.then_some(hitbox)
+        })
+}
+
+// Detection only: whatever a collision leads to is up to `handle_death`
+fn check_for_collisions(
+    player_query: Query<(&GlobalTransform, &Handle<Image>), With<Player>>,
+    colliders: Colliders,
+    images: Res<Assets<Image>>,
+    config: Res<GameConfig>,
+    mut collisions: EventWriter<CollisionEvent>,
+) {
+    let (player_transform, player_sprite) = player_query.single();
+    let player_size = images.get(player_sprite).unwrap().size();
+
+    if let Some((collider_position, collider_size)) = find_collision(
+        player_transform.translation(),
+        player_size,
+        &colliders,
+        &images,
+        &config,
+    ) {
+        collisions.send(CollisionEvent {
+            kind: CollisionKind::Pipe,
+            bird: bird_bounds(
+                &config,
+                player_transform.translation().truncate(),
+                player_size,
+            ),
+            collider: Rect::from_center_size(collider_position.truncate(), collider_size),
+        });
+        warn!(
+            "Collision: player={{pos={} size={}}}, collider={{pos={} size={}}}",
+            player_transform.translation(),
+            player_size,
+            collider_position,
+            collider_size,
+        );
+    }
+}
+
+// Not subject to the collision grace period: nothing can stop a bird from falling into it
+fn check_ground(
+    player: Query<(&Transform, &Handle<Image>), With<Player>>,
+    images: Res<Assets<Image>>,
+    config: Res<GameConfig>,
+    mut collisions: EventWriter<CollisionEvent>,
+) {
+    let (transform, sprite) = player.single();
+    let size = images.get(sprite).map_or(Vec2::ZERO, |image| image.size());
+    let position = transform.translation.truncate();
+    let bird = bird_bounds(&config, position, size);
+    if bird.min.y <= floor_y(&config) {
+        // The floor line spans the view, below the bird wherever the camera is
+        let bounds = visible_bounds(&config);
+        collisions.send(CollisionEvent {
+            kind: CollisionKind::Ground,
+            bird,
+            collider: Rect::new(
+                position.x + bounds.min.x,
+                bounds.min.y,
+                position.x + bounds.max.x,
+                floor_y(&config),
+            ),
+        });
+    }
+}
+
+// Every consequence of the player hitting something goes through here
+#[allow(clippy::too_many_arguments)]
+fn handle_death(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionEvent>,
+    mut player: Query<(&mut Transform, &mut Movable), With<Player>>,
+    pipes: Query<(&Transform, &Pipe), Without<Player>>,
+    next: Res<NextPipe>,
+    config: Res<GameConfig>,
+    daily: Option<Res<DailyChallenge>>,
+    mut mercy: ResMut<Mercy>,
+    mut mercy_label: Query<(&mut Flash, &mut Visibility), With<MercyLabel>>,
+    mut death_cause: ResMut<DeathCause>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    // One hit is as deadly as several
+    let Some(collision) = collisions.iter().last() else {
+        return;
+    };
+
+    let gap = next.0.and_then(|entity| pipes.get(entity).ok());
+    if let (Some((gap, pipe)), false, true, None) =
+        (gap, mercy.used, config.first_death_mercy, &daily)
+    {
+        // Put the bird back in the middle of the gap it was trying to thread
+        let (mut transform, mut movable) = player.single_mut();
+        mercy.used = true;
+        transform.translation.y = pipe.nearest_opening(gap.translation.y, transform.translation.y);
+        movable.velocity = Vec3::ZERO;
+        let (mut flash, mut visibility) = mercy_label.single_mut();
+        flash.0.reset();
+        *visibility = Visibility::Visible;
+        return;
+    }
+
+    info!("Run ended by hitting {:?}", collision.kind);
+    death_cause.0 = Some(collision.kind);
+    if config.show_fatal_hitboxes {
+        match config.bird_hitbox {
+            BirdHitbox::Box => spawn_hitbox_outline(&mut commands, collision.bird, Color::YELLOW),
+            BirdHitbox::Circle { radius } => spawn_circle_outline(
+                &mut commands,
+                collision.bird.center(),
+                radius,
+                Color::YELLOW,
+            ),
+        }
+        spawn_hitbox_outline(&mut commands, collision.collider, Color::RED);
+    }
+    game_state.set(GameState::GameOver);
+}
+
+// Four thin bars along the edges of `hitbox`, leaving what's inside visible
+fn spawn_hitbox_outline(commands: &mut Commands, hitbox: Rect, color: Color) {
+    let center = hitbox.center();
+    let size = hitbox.size();
+    let edges = [
+        (
+            Vec2::new(0.0, size.y / 2.0),
+            Vec2::new(size.x, HITBOX_OUTLINE_WIDTH),
+        ),
+        (
+            Vec2::new(0.0, -size.y / 2.0),
+            Vec2::new(size.x, HITBOX_OUTLINE_WIDTH),
+        ),
+        (
+            Vec2::new(size.x / 2.0, 0.0),
+            Vec2::new(HITBOX_OUTLINE_WIDTH, size.y),
+        ),
+        (
+            Vec2::new(-size.x / 2.0, 0.0),
+            Vec2::new(HITBOX_OUTLINE_WIDTH, size.y),
+        ),
+    ];
+    spawn_outline(commands, center, edges, color);
+}
+
+// A ring of dots, since there's no line drawing to trace it with
+fn spawn_circle_outline(commands: &mut Commands, center: Vec2, radius: f32, color: Color) {
+    let dots = (0..CIRCLE_OUTLINE_DOTS).map(|i| {
+        let angle = i as f32 / CIRCLE_OUTLINE_DOTS as f32 * std::f32::consts::TAU;
+        (
+            Vec2::new(angle.cos(), angle.sin()) * radius,
+            Vec2::splat(2.0 * HITBOX_OUTLINE_WIDTH),
+        )
+    });
+    spawn_outline(commands, center, dots, color);
+}
+
+// Pieces are given as offsets from `center` and sizes
+fn spawn_outline(
+    commands: &mut Commands,
+    center: Vec2,
+    pieces: impl IntoIterator<Item = (Vec2, Vec2)>,
+    color: Color,
+) {
+    commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_translation(
+                center.extend(layers::FATAL_HITBOXES),
+            )),
+            FatalHitbox(Timer::from_seconds(FATAL_HITBOX_DURATION, TimerMode::Once)),
+            RunEntity,
+        ))
+        .with_children(|parent| {
+            for (offset, size) in pieces {
+                parent.spawn(SpriteBundle {
+                    sprite: Sprite {
+                        color,
+                        custom_size: Some(size),
+                        ..default()
+                    },
+                    transform: Transform::from_translation(offset.extend(0.0)),
+                    ..default()
+                });
+            }
+        });
+}
+
+fn clear_fatal_hitboxes(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut hitboxes: Query<(Entity, &mut FatalHitbox)>,
+) {
+    for (entity, mut hitbox) in &mut hitboxes {
+        if hitbox.0.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+}
+
+// Runs after `handle_death`, so reaching the score on the frame of a crash still wins
+fn check_win(
+    config: Res<GameConfig>,
+    score: Res<Score>,
+    mut game_state: ResMut<NextState<GameState>>,
+) {
+    if config
+        .win_score
+        .is_some_and(|win_score| score.0 >= win_score)
+    {
+        game_state.set(GameState::Victory);
+    }
+}
+
+// Offset from a pipe's centre to the line the bird has to cross for the point
+fn score_line(trigger: ScoreTrigger) -> f32 {
+    match trigger {
+        ScoreTrigger::LeadingEdge => -PIPE_WIDTH / 2.0,
+        ScoreTrigger::Center => 0.0,
+        ScoreTrigger::TrailingEdge => PIPE_WIDTH / 2.0,
+    }
+}
+
+fn check_score(
+    config: Res<GameConfig>,
+    player: Query<&Transform, With<Player>>,
+    mut pipes: Query<(&Transform, &mut Pipe), Without<Player>>,
+    mut score: ResMut<Score>,
+) {
+    let Ok(bird) = player.get_single() else {
+        return;
+    };
+    let line = score_line(config.score_trigger);
+    for (transform, mut pipe) in &mut pipes {
+        if !pipe.passed && transform.translation.x + line < bird.translation.x {
+            pipe.passed = true;
+            score.0 += 1;
+        }
+    }
+}
+
+fn set_score_label(score: Res<Score>, mut text: Query<&mut Text, With<ScoreLabel>>) {
+    let mut text = text.single_mut();
+    text.sections[1].value = score.0.to_string();
+}
+
+// Centers the largest viewport of the design aspect ratio that fits the window
+fn fit_viewport(
+    config: Res<GameConfig>,
+    windows: Query<&Window, (With<PrimaryWindow>, Changed<Window>)>,
+    mut cameras: Query<&mut Camera>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    let window_size = UVec2::new(window.physical_width(), window.physical_height());
+    if window_size.x == 0 || window_size.y == 0 {
+        return;
+    }
+    let aspect = config.design_aspect_ratio;
+    let size = if window_size.x as f32 / window_size.y as f32 > aspect {
+        UVec2::new((window_size.y as f32 * aspect) as u32, window_size.y)
+    } else {
+        UVec2::new(window_size.x, (window_size.x as f32 / aspect) as u32)
+    };
+    for mut camera in &mut cameras {
+        camera.viewport = Some(Viewport {
+            physical_position: (window_size - size) / 2,
+            physical_size: size,
+            ..default()
+        });
+    }
+}
+
+// How much bigger than authored the UI is drawn in `window`. Letterboxed, only the playfield
+// between the bars counts, so text keeps its place relative to the pipes
+fn ui_scale(config: &GameConfig, window: &Window) -> f64 {
+    if !config.scale_ui {
+        return 1.0;
+    }
+    let height = if config.letterbox {
+        window
+            .height()
+            .min(window.width() / config.design_aspect_ratio)
+    } else {
+        window.height()
+    };
+    (height / config.ui_design_height).clamp(MIN_UI_SCALE, MAX_UI_SCALE) as f64
+}
+
+// Font sizes are scaled before glyphs are rasterized, so bigger text stays sharp
+fn scale_ui(
+    config: Res<GameConfig>,
+    windows: Query<&Window, (With<PrimaryWindow>, Changed<Window>)>,
+    mut scale: ResMut<UiScale>,
+) {
+    let Ok(window) = windows.get_single() else {
+        return;
+    };
+    // Minimized windows report no size
+    if window.height() <= 0.0 {
+        return;
+    }
+    let new_scale = ui_scale(&config, window);
+    if scale.scale != new_scale {
+        scale.scale = new_scale;
+    }
+}
+
+fn set_streamer_label(
+    score: Res<Score>,
+    achievements: Res<Achievements>,
+    language: Res<Language>,
+    mut text: Query<&mut Text, With<StreamerLabel>>,
+) {
+    let Ok(mut text) = text.get_single_mut() else {
+        return;
+    };
+    text.sections[0].value = score.0.to_string();
+    // The latest few, newest on top
+    text.sections[1].value = achievements
+        .session()
+        .iter()
+        .rev()
+        .take(3)
+        .map(|achievement| format!("\n{}", achievement.title(&language)))
+        .collect();
+}
+
+// Hides every HUD overlay for clean captures, without pausing the game
+fn toggle_hud(keyboard_input: Res<Input<KeyCode>>, mut hud: Query<&mut Visibility, With<HudRoot>>) {
+    if keyboard_input.just_pressed(KeyCode::F10) {
+        let mut visibility = hud.single_mut();
+        *visibility = match *visibility {
+            Visibility::Hidden => Visibility::Inherited,
+            _ => Visibility::Hidden,
+        };
+    }
+}
+
+// Leans the view toward the incoming pipes, further the faster they come
+fn update_camera_lookahead(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    pipes: Query<&Movable, With<Pipe>>,
+    mut camera_offset: ResMut<CameraOffset>,
+) {
+    let target = match pipes.iter().next() {
+        Some(pipe) if config.camera_lookahead && !config.reduce_motion => {
+            speed_progress(pipe) * CAMERA_LOOKAHEAD * visible_size(&config).x
+        }
+        _ => 0.0,
+    };
+    // Eased, so a restart dropping the speed doesn't snap the view back
+    let ease = 1.0 - (-CAMERA_EASING * time.delta_seconds()).exp();
+    let lookahead = camera_offset.lookahead + (target - camera_offset.lookahead) * ease;
+    // Only write on change, so the camera is left alone once the easing has settled
+    if lookahead != camera_offset.lookahead {
+        camera_offset.lookahead = lookahead;
+    }
+}
+
+fn move_camera(
+    camera_offset: Res<CameraOffset>,
+    mut cameras: Query<&mut Transform, With<Camera2d>>,
+) {
+    let offset = camera_offset.total();
+    for mut transform in &mut cameras {
+        transform.translation.x = offset.x;
+        transform.translation.y = offset.y;
+    }
+}
+
+// How far a pipe is along the ramp from its start to its max speed, from 0 to 1
+fn speed_progress(pipe: &Movable) -> f32 {
+    ((pipe.velocity.x.abs() - PIPE_START_SPEED) / (PIPE_MAX_SPEED - PIPE_START_SPEED))
+        .clamp(0.0, 1.0)
+}
+
+// Linear blend between the two stops around `progress`
+fn pipe_tint(stops: &[Color], progress: f32) -> Color {
+    let Some(last) = stops.last() else {
+        return Color::WHITE;
+    };
+    let position = progress.clamp(0.0, 1.0) * (stops.len() - 1) as f32;
+    let index = position as usize;
+    if index + 1 >= stops.len() {
+        return *last;
+    }
+    let from = Vec4::from_array(stops[index].as_rgba_f32());
+    let to = Vec4::from_array(stops[index + 1].as_rgba_f32());
+    let blended = from.lerp(to, position.fract());
+    Color::rgba(blended.x, blended.y, blended.z, blended.w)
+}
+
+// Like the tint, only spawned and recycled pipes change their look
+fn restyle_pipes(
+    looks: Res<PipeLooks>,
+    pipes: Query<(&Pipe, &Children), Changed<Pipe>>,
+    mut pieces: Query<
+        (
+            &mut Handle<Image>,
+            &mut Sprite,
+            Option<&GapEdge>,
+            Option<&Children>,
+        ),
+        Or<(With<Collider>, With<TunnelWall>)>,
+    >,
+) {
+    // Every pipe spawns with the default look
+    if looks.0.len() <= 1 {
+        return;
+    }
+    for (pipe, children) in &pipes {
+        if pipe.passed {
+            continue;
+        }
+        let look = &looks.0[pipe.look];
+        let mut segments = Vec::new();
+        let mut starts = pieces.iter_many_mut(children);
+        while let Some((mut texture, mut sprite, gap_edge, start_children)) = starts.fetch_next() {
+            // The tunnel's middle is a segment
+            let start = gap_edge.is_some();
+            *texture = if start {
+                look.start.clone()
+            } else {
+                look.segment.clone()
+            };
+            sprite.custom_size = look.size(start);
+            segments.extend(start_children.into_iter().flatten().copied());
+        }
+        let mut segments = pieces.iter_many_mut(&segments);
+        while let Some((mut texture, mut sprite, _, _)) = segments.fetch_next() {
+            *texture = look.segment.clone();
+            sprite.custom_size = look.size(false);
+        }
+    }
+}
+
+// Runs on spawned and recycled pipes only, so a pipe keeps one colour while on screen
+fn tint_pipes(
+    config: Res<GameConfig>,
+    pipes: Query<(&Pipe, &Movable, &Children), Changed<Pipe>>,
+    mut pieces: Query<
+        (&mut Sprite, Option<&Children>),
+        Or<(With<Collider>, With<PipeCap>, With<TunnelWall>)>,
+    >,
+) {
+    if !config.pipe_tint_by_speed {
+        return;
+    }
+    for (pipe, movable, children) in &pipes {
+        // Scoring flips `passed` on as well, only resetting it means a recycle
+        if pipe.passed {
+            continue;
+        }
+        let color = pipe_tint(&config.pipe_tint_stops, speed_progress(movable));
+        let mut segments = Vec::new();
+        let mut starts = pieces.iter_many_mut(children);
+        while let Some((mut sprite, start_children)) = starts.fetch_next() {
+            sprite.color = color;
+            segments.extend(start_children.into_iter().flatten().copied());
+        }
+        let mut segments = pieces.iter_many_mut(&segments);
+        while let Some((mut sprite, _)) = segments.fetch_next() {
+            sprite.color = color;
+        }
+    }
+}
+
+fn set_speed_label(
+    language: Res<Language>,
+    pipes: Query<&Movable, With<Pipe>>,
+    mut text: Query<&mut Text, With<SpeedLabel>>,
+) {
+    let Some(pipe) = pipes.iter().next() else {
+        return;
+    };
+    let progress = speed_progress(pipe);
+    let tier = ((progress * SPEED_TIERS as f32) as u32 + 1).min(SPEED_TIERS);
+    let mut text = text.single_mut();
+    let percent = format!("{:.0}", progress * 100.0);
+    text.sections[0].value = language.format("hud.speed", &[&tier, &SPEED_TIERS, &percent]);
+}
 
-fn rotate(mut query: Query<(&mut Transform, &Movable), With<Player>>) {
-    for (mut transform, movable) in &mut query {
-        use std::f32::consts::FRAC_PI_2;
-        let angle =
-            ((movable.velocity.y / UP_SPEED) * ANGLE_AMPLITUDE).clamp(-FRAC_PI_2, FRAC_PI_2);
-        transform.rotation = Quat::from_axis_angle(Vec3::Z, angle);
+// The fill eases after the score rather than jumping a notch per pipe
+fn update_progress_bar(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    score: Res<Score>,
+    mut bar: Query<&mut Visibility, With<ProgressBar>>,
+    mut fill: Query<&mut Style, With<ProgressFill>>,
+) {
+    let mut visibility = bar.single_mut();
+    *visibility = if config.progress_bar && config.spawn_pipes {
+        Visibility::Inherited
+    } else {
+        Visibility::Hidden
+    };
+
+    let mut style = fill.single_mut();
+    let target = (score.0 % MILESTONE_PIPES) as f32 / MILESTONE_PIPES as f32 * 100.0;
+    let Val::Percent(current) = style.size.width else {
+        return;
+    };
+    // Reaching a milestone empties the bar straight away
+    let width = if target < current {
+        target
+    } else {
+        current + (target - current) * (1.0 - (-10.0 * time.delta_seconds()).exp())
+    };
+    style.size.width = Val::Percent(width);
+}
+
+#[allow(clippy::too_many_arguments)]
+fn check_new_best(
+    config: Res<GameConfig>,
+    score: Res<Score>,
+    autopilot: Res<AutopilotControl>,
+    comeback: Res<Comeback>,
+    pipe_rng: Res<PipeRng>,
+    daily: Option<Res<DailyChallenge>>,
+    mut high_score: ResMut<HighScore>,
+    mut slow_motion: ResMut<SlowMotion>,
+    mut flash: Query<(&mut Flash, &mut Visibility), With<NewBestLabel>>,
+) {
+    // Daily runs are up against the day's best, which is what they're saved to
+    let best = match &daily {
+        Some(daily) => daily.best().unwrap_or(0),
+        None => high_score.value,
+    };
+    // A first run with no stored best has nothing to beat
+    if !counts_for_records(&config, &autopilot, &comeback, &pipe_rng, daily.as_deref())
+        || high_score.beaten
+        || best == 0
+        || score.0 <= best
+    {
+        return;
+    }
+    high_score.beaten = true;
+    let (mut flash, mut visibility) = flash.single_mut();
+    flash.0.reset();
+    *visibility = Visibility::Visible;
+    if !config.reduce_motion {
+        slow_motion.0 = Timer::from_seconds(SLOW_MOTION_DURATION, TimerMode::Once);
     }
 }
 
-fn apply_acceleration(time: Res<Time>, mut query: Query<&mut Movable>) {
-    let dt = time.delta_seconds();
-    for mut movable in &mut query {
-        movable.velocity = movable.velocity + movable.acceleration * dt;
+// Ticks in real time, since it's the thing bending game time
+fn update_slow_motion(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    mut slow_motion: ResMut<SlowMotion>,
+    mut time_scale: ResMut<TimeScale>,
+) {
+    let progress = slow_motion.0.tick(time.delta()).percent();
+    // Ease out, so most of the slow-down happens right at the moment of the record
+    let dip = (1.0 - progress).powi(2);
+    time_scale.0 = base_time_scale(&config) * (1.0 - (1.0 - SLOW_MOTION_SCALE) * dip);
+}
+
+fn fade_flashes(time: Res<Time>, mut flashes: Query<(&mut Flash, &mut Visibility)>) {
+    for (mut flash, mut visibility) in &mut flashes {
+        if flash.0.tick(time.delta()).just_finished() {
+            *visibility = Visibility::Hidden;
+        }
     }
 }
 
-fn apply_velocity(time: Res<Time>, mut query: Query<(&Movable, &mut Transform)>) {
-    let dt = time.delta_seconds();
-    for (movable, mut transform) in &mut query {
-        transform.translation += movable.velocity * dt;
+// Whether the run counts for the high score, the other records and achievements. Runs with any
+// help don't, and neither do fixed seeds unless told to; daily runs count for the day's best
+fn counts_for_records(
+    config: &GameConfig,
+    autopilot: &AutopilotControl,
+    comeback: &Comeback,
+    pipe_rng: &PipeRng,
+    daily: Option<&DailyChallenge>,
+) -> bool {
+    let assisted =
+        autopilot.assisted() || config.ground_assist || config.practice || comeback.easing(config);
+    !assisted && (daily.is_some() || !pipe_rng.fixed || config.locked_seed_counts_for_best)
+}
+
+fn save_high_score(
+    config: Res<GameConfig>,
+    score: Res<Score>,
+    autopilot: Res<AutopilotControl>,
+    comeback: Res<Comeback>,
+    pipe_rng: Res<PipeRng>,
+    mut high_score: ResMut<HighScore>,
+    daily: Option<ResMut<DailyChallenge>>,
+) {
+    if !counts_for_records(&config, &autopilot, &comeback, &pipe_rng, daily.as_deref()) {
+        return;
+    }
+    // Daily runs keep their own per-day best
+    if let Some(mut daily) = daily {
+        daily.record(score.0);
+        return;
+    }
+    if score.0 <= high_score.value {
+        return;
+    }
+    high_score.value = score.0;
+    if let Err(err) = save::write_atomic(HIGH_SCORE_PATH, high_score.value.to_string()) {
+        error!("Failed to save high score to {}: {}", HIGH_SCORE_PATH, err);
     }
 }
 
-fn reuse_pipes(mut query: Query<&mut Transform, With<Pipe>>) {
-    let left_border = -WINDOW_SIZE.get().unwrap().width() / 2.0 - 100.0;
-    let mut farther_position = query
-        .iter()
-        .map(|x| x.translation)
-        .max_by(|t1, t2| t1.x.partial_cmp(&t2.x).unwrap())
-        .unwrap();
-    for mut transform in &mut query {
-        if transform.translation.x < left_border {
-            transform.translation = farther_position;
-            transform.translation.x += PIPE_GAP;
-            farther_position = transform.translation;
-        }
+fn count_attempt(mut attempts: ResMut<Attempts>) {
+    attempts.session += 1;
+    attempts.lifetime += 1;
+    if let Err(err) = save::write_atomic(ATTEMPTS_PATH, attempts.lifetime.to_string()) {
+        error!("Failed to save attempts to {}: {}", ATTEMPTS_PATH, err);
     }
 }
 
-fn check_for_collisions(
-    player_query: Query<(&GlobalTransform, &Handle<Image>), With<Player>>,
-    colliders: Query<(&GlobalTransform, &Handle<Image>), With<Collider>>,
-    images: Res<Assets<Image>>,
-    mut game_state: ResMut<NextState<GameState>>,
+// One line per run: Unix time in seconds, points, seconds survived, the seed in the same hex as the
+// HUD, and what ended the run
+fn log_run(
+    config: Res<GameConfig>,
+    state: Res<State<GameState>>,
+    score: Res<Score>,
+    run_time: Res<RunTime>,
+    pipe_rng: Res<PipeRng>,
+    death_cause: Res<DeathCause>,
 ) {
-    use bevy::sprite::collide_aabb::*;
-    let (player_transform, player_sprite) = player_query.single();
-    let player_size = images.get(player_sprite).unwrap().size();
+    if !config.run_log {
+        return;
+    }
+    let timestamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs());
+    let ended_by = match (&state.0, death_cause.0) {
+        (GameState::Victory, _) => "win",
+        (_, Some(CollisionKind::Pipe)) => "pipe",
+        (_, Some(CollisionKind::Ground)) => "ground",
+        (_, None) => "unknown",
+    };
+    let line = format!(
+        "{},{},{:.2},{:016X},{}",
+        timestamp, score.0, run_time.0, pipe_rng.seed, ended_by
+    );
+    if let Err(err) = save::append_line(RUN_LOG_PATH, RUN_LOG_HEADER, &line) {
+        error!("Failed to log run to {}: {}", RUN_LOG_PATH, err);
+    }
+}
 
-    for (transform, sprite) in &colliders {
-        let image = images.get(sprite).unwrap();
-        let collider_size = image.size();
+fn announce_attempt(
+    config: Res<GameConfig>,
+    language: Res<Language>,
+    attempts: Res<Attempts>,
+    mut label: Query<(&mut Flash, &mut Text, &mut Visibility), With<AttemptLabel>>,
+) {
+    if !config.attempt_counter {
+        return;
+    }
+    let (mut flash, mut text, mut visibility) = label.single_mut();
+    text.sections[0].value = language.format("hud.attempt", &[&(attempts.session + 1)]);
+    flash.0.reset();
+    *visibility = Visibility::Visible;
+}
 
-        if collide(
-            player_transform.translation(),
-            player_size,
-            transform.translation(),
-            collider_size,
+#[allow(clippy::too_many_arguments)]
+fn show_game_over(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    config: Res<GameConfig>,
+    language: Res<Language>,
+    score: Res<Score>,
+    pipe_rng: Res<PipeRng>,
+    attempts: Res<Attempts>,
+    glide: Res<glide::GlideStreak>,
+    survival: Res<survival::SurvivalRecord>,
+    run_time: Res<RunTime>,
+    death_cause: Res<DeathCause>,
+    replay: Res<InstantReplay>,
+) {
+    let code = ShareCode {
+        seed: pipe_rng.seed,
+        score: score.0,
+    };
+    // Printed as well, since the log is the easiest place to copy it from
+    info!("Share code: {}", code);
+
+    let attempts = if config.attempt_counter {
+        format!(
+            "\n{}",
+            language.format(
+                "game_over.attempts",
+                &[&attempts.session, &attempts.lifetime]
+            )
         )
-        .is_some()
-        {
-            game_state.set(GameState::GameOver);
-            warn!(
-                "Collision: player={{pos={} size={}}}, collider={{pos={} size={}}}",
-                player_transform.translation(),
-                player_size,
-                transform.translation(),
-                collider_size,
-            );
-        }
+    } else {
+        String::new()
+    };
+    let cause = match death_cause.0 {
+        Some(kind) if config.death_message => format!("{}\n", language.t(death_message(kind))),
+        _ => String::new(),
+    };
+    let glide = format!(
+        "\n{}",
+        language.format(
+            "game_over.glide",
+            &[&glide.run_best(), &glide.lifetime_best()]
+        )
+    );
+    let survival = if !config.survival_record {
+        String::new()
+    } else if survival.set_this_run() {
+        format!(
+            "\n{}",
+            language.format(
+                "game_over.survival_record",
+                &[&format!("{:.1}", run_time.0)]
+            )
+        )
+    } else {
+        format!(
+            "\n{}",
+            language.format(
+                "game_over.survival",
+                &[
+                    &format!("{:.1}", run_time.0),
+                    &format!("{:.1}", survival.best())
+                ]
+            )
+        )
+    };
+
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    commands
+        .spawn((
+            TextBundle::from_sections([
+                TextSection::new(
+                    format!("{}\n", language.t("game_over.title")),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 80.0,
+                        color: Color::BLACK,
+                    },
+                ),
+                TextSection::new(
+                    cause,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 40.0,
+                        color: Color::BLACK,
+                    },
+                ),
+                TextSection::new(
+                    language.format("game_over.share_code", &[&code]),
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 30.0,
+                        color: Color::BLACK,
+                    },
+                ),
+                TextSection::new(
+                    attempts,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 30.0,
+                        color: Color::BLACK,
+                    },
+                ),
+                TextSection::new(
+                    glide,
+                    TextStyle {
+                        font: font.clone(),
+                        font_size: 30.0,
+                        color: Color::BLACK,
+                    },
+                ),
+                TextSection::new(
+                    survival,
+                    TextStyle {
+                        font,
+                        font_size: 30.0,
+                        color: Color::BLACK,
+                    },
+                ),
+            ])
+            .with_text_alignment(TextAlignment::Center)
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                margin: UiRect {
+                    left: Val::Percent(30.),
+                    top: Val::Percent(35.),
+                    ..default()
+                },
+                ..default()
+            }),
+            GameOverScreen,
+        ))
+        .insert(game_over_visibility(&replay));
+}
+
+fn death_message(kind: CollisionKind) -> &'static str {
+    match kind {
+        CollisionKind::Pipe => "death.pipe",
+        CollisionKind::Ground => "death.ground",
     }
 }
 
-fn check_score(
-    mut pipes: Local<Vec<Entity>>,
-    mut current_pipe: Local<usize>,
-    pipes_query: Query<(Entity, &GlobalTransform), With<Pipe>>,
-    mut score: ResMut<Score>,
-) { if pipes.len() == 0 { let mut sorted = pipes_query
-            .iter()
-            .collect::<Vec<_>>();
-        sorted.sort_unstable_by(|(_, t1), (_, t2)| t1.translation().x.partial_cmp(&t2.translation().x).unwrap());
-        *pipes = sorted
-            .into_iter()
-            .map(|(e, _)| e)
-            .collect();
-        *current_pipe = 0;
+// `--speed-curve "<curve>"` swaps the linear speed ramp for a custom one
+fn speed_curve_from_args() -> Option<SpeedCurve> {
+    let curve = std::env::args()
+        .skip_while(|arg| arg != "--speed-curve")
+        .nth(1)?;
+    match curve.parse() {
+        Ok(curve) => Some(curve),
+        Err(err) => {
+            eprintln!("Ignoring speed curve {:?}: {}", curve, err);
+            None
+        }
     }
+}
 
-    let Ok((_, pipe_transform)) = pipes_query.get(pipes[*current_pipe]) else {
-        error!("Failed to get current pipe({:?}) from pipes: {:?}", current_pipe, pipes_query);
+// All pipes share one speed, so they're all set together rather than as they recycle
+fn follow_speed_curve(
+    config: Res<GameConfig>,
+    run_time: Res<RunTime>,
+    score: Res<Score>,
+    mut pipes: Query<&mut Movable, With<Pipe>>,
+) {
+    let Some(curve) = &config.speed_curve else {
         return;
     };
+    let speed = curve.sample(match curve.input {
+        CurveInput::Time => run_time.0,
+        CurveInput::Score => score.0 as f32,
+    });
+    for mut pipe in &mut pipes {
+        pipe.velocity.x = -speed;
+    }
+}
+
+fn show_victory(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    language: Res<Language>,
+    bindings: Res<KeyBindings>,
+    score: Res<Score>,
+    run_time: Res<RunTime>,
+    attempts: Res<Attempts>,
+) {
+    info!("Run won with {} points", score.0);
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    commands.spawn((
+        TextBundle::from_sections([
+            TextSection::new(
+                format!("{}\n", language.t("victory.title")),
+                TextStyle {
+                    font: font.clone(),
+                    font_size: 80.0,
+                    color: Color::GOLD,
+                },
+            ),
+            TextSection::new(
+                format!(
+                    "{}\n{}",
+                    language.format(
+                        "victory.summary",
+                        &[&score.0, &format!("{:.0}", run_time.0), &attempts.session]
+                    ),
+                    language.format("victory.restart", &[&format!("{:?}", bindings.restart)])
+                ),
+                TextStyle {
+                    font,
+                    font_size: 30.0,
+                    color: Color::BLACK,
+                },
+            ),
+        ])
+        .with_text_alignment(TextAlignment::Center)
+        .with_style(Style {
+            position_type: PositionType::Absolute,
+            margin: UiRect {
+                left: Val::Percent(30.),
+                top: Val::Percent(35.),
+                ..default()
+            },
+            ..default()
+        }),
+        // Cleared along with the game-over screen
+        GameOverScreen,
+    ));
+}
+
+// A code passed as `--share-code <code>` replays that run's layout, and `--seed <seed>` locks in
+// a seed as shown on the HUD
+// `--benchmark`, only there with the debug tools, plays a scripted session
+fn benchmarking() -> bool {
+    cfg!(feature = "debug") && std::env::args().any(|arg| arg == "--benchmark")
+}
+
+fn seed_from_args() -> Option<u64> {
+    if let Some(seed) = std::env::args().skip_while(|arg| arg != "--seed").nth(1) {
+        match u64::from_str_radix(&seed, 16) {
+            Ok(seed) => return Some(seed),
+            Err(err) => eprintln!("Ignoring seed {:?}: {}", seed, err),
+        }
+    }
+    let code = std::env::args()
+        .skip_while(|arg| arg != "--share-code")
+        .nth(1)?;
+    match code.parse::<ShareCode>() {
+        Ok(code) => Some(code.seed),
+        Err(err) => {
+            eprintln!("Ignoring share code {:?}: {}", code, err);
+            None
+        }
+    }
+}
+
+// Locking mid-run keeps the layout being flown, so it can be practiced from the next restart on
+fn toggle_seed_lock(
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    daily: Option<Res<DailyChallenge>>,
+    mut pipe_rng: ResMut<PipeRng>,
+) {
+    // The daily layout is the same for everyone and can't be unlocked
+    if daily.is_some() || !keyboard_input.just_pressed(bindings.seed_lock) {
+        return;
+    }
+    pipe_rng.fixed = !pipe_rng.fixed;
+}
 
-    if pipe_transform.translation().x < 0.0 {
-        score.0 += 1;
-        *current_pipe = (*current_pipe + 1) % PIPE_COUNT;
+fn set_seed_label(
+    language: Res<Language>,
+    pipe_rng: Res<PipeRng>,
+    daily: Option<Res<DailyChallenge>>,
+    mut label: Query<(&mut Text, &mut Visibility), With<SeedLabel>>,
+) {
+    let (mut text, mut visibility) = label.single_mut();
+    if pipe_rng.fixed && daily.is_none() {
+        let seed = format!("{:016X}", pipe_rng.seed);
+        text.sections[0].value = language.format("hud.seed_locked", &[&seed]);
+        *visibility = Visibility::Inherited;
+    } else {
+        *visibility = Visibility::Hidden;
     }
 }
 
-fn set_score_label(score: Res<Score>, mut text: Query<&mut Text>) {
+fn set_daily_label(
+    language: Res<Language>,
+    daily: Option<Res<DailyChallenge>>,
+    mut label: Query<&mut Text, With<DailyLabel>>,
+) {
+    let (Some(daily), Ok(mut text)) = (daily, label.get_single_mut()) else {
+        return;
+    };
+    let best = match daily.best() {
+        Some(best) => language.format("hud.daily_best", &[&best]),
+        None => language.t("hud.daily_untried").to_string(),
+    };
+    text.sections[1].value = format!("{} - {}", daily.date(), best);
+}
+
+fn set_high_score_label(
+    high_score: Res<HighScore>,
+    mut text: Query<&mut Text, With<HighScoreLabel>>,
+) {
     let mut text = text.single_mut();
-    text.sections[1].value = score.0.to_string();
+    text.sections[1].value = high_score.value.to_string();
 }
 
 fn main() {
@@ -353,38 +3270,695 @@ fn main() {
         .set(WindowResolution::new(1280.0, 720.0))
         .expect("Could not initialize window resolution");
 
-    App::new()
-        .add_plugins(DefaultPlugins.set(WindowPlugin {
-            primary_window: Some(Window {
-                resolution: WINDOW_SIZE.get().unwrap().clone(),
-                mode: WindowMode::Windowed,
-                ..default()
-            }),
+    // The benchmark plays the same session whatever the player has saved, and keeps it out of
+    // the attempt count and the run log
+    let benchmarking = benchmarking();
+    let mut settings = if benchmarking {
+        Settings::default()
+    } else {
+        Settings::load()
+    };
+    // Relaxed flying with nothing to dodge; Esc still quits as usual
+    if std::env::args().any(|arg| arg == "--zen") {
+        settings.launch_mode = Some(GameMode::Zen);
+    }
+    let mut config = GameConfig::default();
+    settings.apply(&mut config);
+    if let Some(curve) = speed_curve_from_args() {
+        config.speed_curve = Some(curve);
+    }
+    // `--theme <dir>` loads sprites from `assets/<dir>` where it has them
+    if let Some(theme) = std::env::args().skip_while(|arg| arg != "--theme").nth(1) {
+        config.theme = Some(theme);
+    }
+    let daily = std::env::args()
+        .any(|arg| arg == "--daily")
+        .then(DailyChallenge::today);
+    let pipe_rng = match (&daily, seed_from_args()) {
+        (Some(daily), _) => PipeRng::new(daily.seed(), true),
+        (None, Some(seed)) => PipeRng::new(seed, true),
+        (None, None) => PipeRng::new(rand::thread_rng().gen(), false),
+    };
+
+    let mut app = App::new();
+    app.add_plugins(DefaultPlugins.set(WindowPlugin {
+        primary_window: Some(Window {
+            title: config.window_title.clone(),
+            resolution: WINDOW_SIZE.get().unwrap().clone(),
+            // Sized, so the playfield keeps the resolution everything is laid out for
+            mode: if settings.fullscreen {
+                WindowMode::SizedFullscreen
+            } else {
+                WindowMode::Windowed
+            },
             ..default()
-        }))
-        .add_startup_system(startup)
-        .init_resource::<Score>()
-        .add_state::<GameState>()
-        // .init_resource::<LoadingAssets>()
-        // .add_system(post_loading)
-        .add_systems(
-            (
-                jump,
-                apply_acceleration.after(jump),
-                apply_velocity.after(apply_acceleration),
-                rotate.after(apply_acceleration),
-                reuse_pipes,
-                check_for_collisions.run_if(|mut timer: Local<f32>, time: Res<Time>| {
-                    // Tick the timer
-                    *timer += time.delta_seconds();
-                    // Return true if the timer has passed the time
-                    *timer >= 1.0
-                }),
-                check_score,
-                set_score_label.after(check_score).run_if(resource_changed::<Score>()),
-            )
+        }),
+        ..default()
+    }))
+    .add_startup_system(startup)
+    .init_resource::<Score>()
+    .insert_resource(Msaa::from(config.msaa))
+    .insert_resource(TimeScale(base_time_scale(&config)))
+    .init_resource::<SlowMotion>()
+    .init_resource::<CameraOffset>()
+    .insert_resource(config)
+    .insert_resource(HighScore::load())
+    .insert_resource(Attempts::load())
+    .init_resource::<NextPipe>()
+    .init_resource::<PipeLooks>()
+    .insert_resource(pipe_rng)
+    .init_resource::<RunTime>()
+    .insert_resource(settings.key_bindings.clone())
+    .insert_resource(Language::load(&settings.language))
+    .insert_resource(settings)
+    .init_resource::<Mercy>()
+    .init_resource::<DeathCause>()
+    .add_event::<CollisionEvent>()
+    .add_event::<FlapEvent>()
+    .add_state::<GameState>()
+    // .init_resource::<LoadingAssets>()
+    // .add_system(post_loading)
+    .add_systems(
+        (
+            jump.run_if(preload::ready),
+            apply_acceleration.after(jump).run_if(run_started),
+            apply_velocity.after(apply_acceleration).run_if(run_started),
+            rotate.after(apply_acceleration),
+            squash_and_stretch.after(jump),
+            reuse_pipes.run_if(spawns_pipes),
+            scroll_background,
+            scroll_ground,
+            update_next_pipe
+                .after(reuse_pipes)
+                .after(apply_velocity)
+                .run_if(spawns_pipes),
+            check_for_collisions.run_if(|run_time: Res<RunTime>| run_time.0 >= COLLISION_GRACE),
+            check_ground.after(apply_velocity).run_if(spawns_pipes),
+            handle_death.after(check_for_collisions).after(check_ground),
+            check_score.run_if(spawns_pipes),
+            set_score_label
+                .after(check_score)
+                .run_if(resource_changed::<Score>()),
+        )
             .in_set(OnUpdate(GameState::Playing)),
+    )
+    .add_systems(
+        (
+            update_gap_guide.after(update_next_pipe),
+            bob_hovering.before(jump),
+            update_gap_telegraph.after(reuse_pipes),
+            update_ghost_pipe.after(reuse_pipes),
+            update_camera_lookahead.after(apply_acceleration),
+            update_progress_bar.after(check_score),
+            update_trajectory.after(apply_velocity),
+            tint_pipes.after(reuse_pipes).run_if(spawns_pipes),
+            collect_gravity_flip.after(apply_velocity),
+            tick_gravity_flip.before(jump),
+            check_new_best.after(check_score),
+            set_speed_label.after(apply_acceleration),
+            keep_in_view
+                .after(apply_velocity)
+                .run_if(|config: Res<GameConfig>| !config.spawn_pipes),
         )
-        .add_system(bevy::window::close_on_esc)
-        .run();
+            .in_set(OnUpdate(GameState::Playing)),
+    )
+    // Ahead of everything that reads the time scale, so the whole frame agrees on it
+    .add_system(
+        update_slow_motion
+            .in_base_set(CoreSet::PreUpdate)
+            .run_if(in_state(GameState::Playing)),
+    )
+    .add_system(
+        tick_run_time
+            .run_if(run_started)
+            .in_set(OnUpdate(GameState::Playing)),
+    )
+    .add_system(
+        restyle_pipes
+            .after(reuse_pipes)
+            .run_if(spawns_pipes)
+            .in_set(OnUpdate(GameState::Playing)),
+    )
+    .add_system(
+        ground_assist
+            .after(jump)
+            .before(apply_acceleration)
+            .in_set(OnUpdate(GameState::Playing)),
+    )
+    .add_system(
+        check_win
+            .after(check_score)
+            .after(handle_death)
+            .in_set(OnUpdate(GameState::Playing)),
+    )
+    .add_system(
+        follow_speed_curve
+            .before(apply_velocity)
+            .in_set(OnUpdate(GameState::Playing)),
+    )
+    .add_system(move_camera.run_if(resource_changed::<CameraOffset>()))
+    .add_system(spawn_run.in_schedule(OnEnter(GameState::Playing)))
+    .add_system(announce_attempt.in_schedule(OnEnter(GameState::Playing)))
+    // The first press while the replay runs only skips it
+    .add_system(
+        restart
+            .before(instant_replay::play)
+            .in_set(OnUpdate(GameState::GameOver))
+            .run_if(|replay: Res<InstantReplay>| !replay.playing()),
+    )
+    .add_system(restart.in_set(OnUpdate(GameState::Victory)))
+    .add_system(clear_fatal_hitboxes.in_set(OnUpdate(GameState::GameOver)))
+    .add_system(reset_run.in_schedule(OnExit(GameState::GameOver)))
+    .add_system(reset_run.in_schedule(OnExit(GameState::Victory)))
+    // Only when switching game modes mid-run: the state is already the next one while exiting
+    .add_system(
+        reset_run
+            .in_schedule(OnExit(GameState::Playing))
+            .run_if(in_state(GameState::Playing)),
+    )
+    .add_system(save_high_score.in_schedule(OnEnter(GameState::GameOver)))
+    .add_system(
+        count_attempt
+            .run_if(move || !benchmarking)
+            .in_schedule(OnEnter(GameState::GameOver)),
+    )
+    .add_system(save_high_score.in_schedule(OnEnter(GameState::Victory)))
+    .add_system(
+        count_attempt
+            .run_if(move || !benchmarking)
+            .in_schedule(OnEnter(GameState::Victory)),
+    )
+    .add_system(
+        log_run
+            .run_if(move || !benchmarking)
+            .in_schedule(OnEnter(GameState::GameOver)),
+    )
+    .add_system(
+        log_run
+            .run_if(move || !benchmarking)
+            .in_schedule(OnEnter(GameState::Victory)),
+    )
+    .add_system(
+        spawn_restart_hold
+            .after(instant_replay::start)
+            .in_schedule(OnEnter(GameState::GameOver)),
+    )
+    .add_system(spawn_restart_hold.in_schedule(OnEnter(GameState::Victory)))
+    .add_system(
+        show_victory
+            .after(count_attempt)
+            .in_schedule(OnEnter(GameState::Victory)),
+    )
+    .add_system(
+        show_game_over
+            .after(count_attempt)
+            .after(instant_replay::start)
+            .in_schedule(OnEnter(GameState::GameOver)),
+    )
+    .add_system(set_high_score_label.run_if(resource_changed::<HighScore>()))
+    // Only daily runs have the label, and each one that ends may set a new best for the day
+    .add_system(set_daily_label.run_if(
+        resource_exists_and_changed::<DailyChallenge>().or_else(resource_changed::<Language>()),
+    ))
+    .add_system(toggle_seed_lock)
+    .add_system(
+        set_seed_label
+            .after(toggle_seed_lock)
+            .run_if(resource_changed::<PipeRng>().or_else(resource_changed::<Language>())),
+    )
+    .add_plugin(achievements::AchievementsPlugin)
+    .add_plugin(animation::AnimationPlugin)
+    .add_plugin(autopilot::AutopilotPlugin)
+    .add_plugin(checkpoint::CheckpointPlugin)
+    .add_plugin(comeback::ComebackPlugin)
+    .add_plugin(event_log::EventLogPlugin)
+    .add_plugin(glide::GlidePlugin)
+    .add_plugin(icon::IconPlugin)
+    .add_plugin(instant_replay::InstantReplayPlugin)
+    .add_plugin(music::MusicPlugin)
+    .add_plugin(particles::ParticlesPlugin)
+    .add_plugin(performance::PerformancePlugin)
+    .add_plugin(preload::PreloadPlugin)
+    .add_plugin(stages::StagesPlugin)
+    .add_plugin(survival::SurvivalPlugin)
+    .add_plugin(theme::ThemePlugin)
+    .add_plugin(transition::TransitionPlugin)
+    .add_plugin(tumble::TumblePlugin)
+    .add_system(toggle_hud)
+    .add_system(set_streamer_label.run_if(|config: Res<GameConfig>| config.streamer_mode))
+    .add_system(fit_viewport.run_if(|config: Res<GameConfig>| config.letterbox))
+    .add_system(scale_ui)
+    .add_system(fade_flashes)
+    .add_system(lang::relabel.run_if(resource_changed::<Language>()))
+    .add_system(settings::reset_settings)
+    .add_system(settings::cycle_language.after(settings::reset_settings))
+    .add_system(
+        settings::cycle_effects_intensity
+            .after(settings::reset_settings)
+            .before(settings::apply_settings),
+    )
+    .add_system(
+        settings::switch_game_mode
+            .after(settings::cycle_language)
+            .before(settings::apply_settings),
+    )
+    .add_system(settings::apply_settings.after(settings::reset_settings))
+    .add_system(settings::save_settings.after(settings::reset_settings))
+    .add_system(bevy::window::close_on_esc);
+    #[cfg(feature = "debug")]
+    app.add_plugin(flock::FlockPlugin)
+        .add_plugin(latency::LatencyPlugin)
+        .add_plugin(time_control::TimeControlPlugin)
+        .add_plugin(tuning::TuningPlugin);
+    #[cfg(feature = "debug")]
+    if let Some(benchmark) = benchmark::BenchmarkPlugin::from_args() {
+        app.add_plugin(benchmark);
+    }
+    #[cfg(feature = "sky_shader")]
+    app.add_plugin(sky::SkyPlugin);
+    if let Some(daily) = daily {
+        app.insert_resource(daily);
+    }
+    app.run();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // What `main` would have set, for everything that reads the view size
+    fn init_window() {
+        WINDOW_SIZE.get_or_init(|| WindowResolution::new(1280.0, 720.0));
+    }
+
+    #[test]
+    fn circle_inside_rect_overlaps() {
+        let rect = Rect::new(-10.0, -10.0, 10.0, 10.0);
+        assert!(circle_overlaps_rect(Vec2::ZERO, 1.0, rect));
+    }
+
+    #[test]
+    fn circle_beside_rect_misses() {
+        let rect = Rect::new(-10.0, -10.0, 10.0, 10.0);
+        assert!(!circle_overlaps_rect(Vec2::new(20.0, 0.0), 5.0, rect));
+    }
+
+    #[test]
+    fn circle_near_corner_uses_corner_distance() {
+        let rect = Rect::new(-10.0, -10.0, 10.0, 10.0);
+        // Within the radius of both edges' lines, but not of the corner itself
+        let center = Vec2::new(14.0, 14.0);
+        assert!(!circle_overlaps_rect(center, 5.0, rect));
+        assert!(circle_overlaps_rect(center, 6.0, rect));
+    }
+
+    #[test]
+    fn circle_touching_edge_overlaps() {
+        let rect = Rect::new(-10.0, -10.0, 10.0, 10.0);
+        assert!(circle_overlaps_rect(Vec2::new(15.0, 0.0), 5.0, rect));
+    }
+
+    #[test]
+    fn playfield_is_centred_on_the_origin() {
+        for (width, height) in [(1280.0, 720.0), (720.0, 1280.0), (1.0, 1.0)] {
+            let bounds = playfield_bounds(width, height);
+            assert_eq!(bounds.center(), Vec2::ZERO);
+            assert_eq!(bounds.size(), Vec2::new(width, height));
+        }
+    }
+
+    #[test]
+    fn borders_sit_buffer_past_each_edge() {
+        for width in [640.0, 1280.0, 1920.0] {
+            for buffer in [0.0, 100.0, 250.0] {
+                assert_eq!(left_border(width, buffer), -width / 2.0 - buffer);
+                assert_eq!(right_border(width, buffer), width / 2.0 + buffer);
+            }
+        }
+    }
+
+    // There is no live resize yet, so this covers what one would recompute: everything follows
+    // the new width and height, and pipes still spawn and recycle out of sight
+    #[test]
+    fn borders_follow_a_resolution_change() {
+        let buffer = 100.0;
+        let (before, after) = (Vec2::new(1280.0, 720.0), Vec2::new(1920.0, 1080.0));
+        let grown = (after.x - before.x) / 2.0;
+        assert_eq!(
+            left_border(after.x, buffer),
+            left_border(before.x, buffer) - grown
+        );
+        assert_eq!(
+            right_border(after.x, buffer),
+            right_border(before.x, buffer) + grown
+        );
+
+        let bounds = playfield_bounds(after.x, after.y);
+        assert_eq!(bounds.max.y, after.y / 2.0);
+        assert_eq!(bounds.min.y, -after.y / 2.0);
+        assert!(left_border(after.x, buffer) < bounds.min.x);
+        assert!(right_border(after.x, buffer) > bounds.max.x);
+    }
+
+    fn layout(xs: &[f32]) -> Vec<(Entity, Vec3)> {
+        xs.iter()
+            .enumerate()
+            .map(|(i, x)| (Entity::from_raw(i as u32), Vec3::new(*x, 0.0, 0.0)))
+            .collect()
+    }
+
+    #[test]
+    fn next_pipe_is_the_nearest_ahead() {
+        let pipes = layout(&[900.0, 300.0, 600.0]);
+        assert_eq!(next_pipe(0.0, pipes).unwrap().0, Entity::from_raw(1));
+    }
+
+    #[test]
+    fn next_pipe_includes_one_partly_behind_the_bird() {
+        // Centred behind the bird, but its right half still has to be cleared
+        let pipes = layout(&[-PIPE_WIDTH / 4.0, 300.0]);
+        assert_eq!(next_pipe(0.0, pipes).unwrap().0, Entity::from_raw(0));
+        // Once its trailing edge is behind the bird by any amount, the next one takes over
+        let pipes = layout(&[-PIPE_WIDTH / 2.0 - 1.0, 300.0]);
+        assert_eq!(next_pipe(0.0, pipes).unwrap().0, Entity::from_raw(1));
+    }
+
+    #[test]
+    fn no_next_pipe_once_all_are_behind() {
+        assert!(next_pipe(1000.0, layout(&[-300.0, 0.0, 300.0])).is_none());
+        assert!(next_pipe(0.0, layout(&[])).is_none());
+    }
+
+    #[test]
+    fn next_pipe_ties_go_to_the_first() {
+        // A trailing edge exactly at the bird still counts as ahead
+        let pipes = layout(&[-PIPE_WIDTH / 2.0, 300.0]);
+        assert_eq!(next_pipe(0.0, pipes).unwrap().0, Entity::from_raw(0));
+        let pipes = layout(&[500.0, 200.0, 200.0]);
+        assert_eq!(next_pipe(0.0, pipes).unwrap().0, Entity::from_raw(1));
+    }
+
+    // Score after one `check_score` with the bird at `bird_x` and a single pipe centred at 0
+    fn score_with_bird_at(trigger: ScoreTrigger, bird_x: f32) -> u32 {
+        let mut app = App::new();
+        app.insert_resource(GameConfig {
+            score_trigger: trigger,
+            ..default()
+        })
+        .init_resource::<Score>()
+        .add_system(check_score);
+        app.world
+            .spawn((Transform::from_xyz(bird_x, 0.0, 0.0), Player));
+        app.world.spawn((Transform::default(), Pipe::default()));
+        app.update();
+        app.world.resource::<Score>().0
+    }
+
+    #[test]
+    fn each_score_trigger_awards_past_its_line() {
+        for (trigger, line) in [
+            (ScoreTrigger::LeadingEdge, -PIPE_WIDTH / 2.0),
+            (ScoreTrigger::Center, 0.0),
+            (ScoreTrigger::TrailingEdge, PIPE_WIDTH / 2.0),
+        ] {
+            assert_eq!(score_with_bird_at(trigger, line - 1.0), 0, "{:?}", trigger);
+            assert_eq!(score_with_bird_at(trigger, line + 1.0), 1, "{:?}", trigger);
+        }
+    }
+
+    // Stands in for the pipe speed, in fixed steps so the test doesn't depend on frame times
+    fn scroll_pipes(mut pipes: Query<&mut Transform, With<Pipe>>) {
+        for mut transform in &mut pipes {
+            transform.translation.x -= 10.0;
+        }
+    }
+
+    #[test]
+    fn pipe_scores_once_and_again_after_recycling() {
+        init_window();
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<Image>()
+            // A lone pipe is recycled behind itself, so this far apart it comes back ahead
+            .insert_resource(GameConfig {
+                pipe_spacing_min: 2000.0,
+                pipe_spacing_max: 2000.0,
+                ..default()
+            })
+            .insert_resource(PipeRng::new(1, true))
+            .init_resource::<Score>()
+            .init_resource::<CameraOffset>()
+            .init_resource::<Comeback>()
+            .init_resource::<PipeLooks>()
+            .add_system(scroll_pipes)
+            .add_system(check_score.after(scroll_pipes))
+            .add_system(reuse_pipes.after(check_score));
+        app.world.spawn((Transform::default(), Player));
+        let pipe = app
+            .world
+            .spawn((
+                Transform::from_xyz(100.0, 0.0, 0.0),
+                Pipe::default(),
+                Movable::default(),
+            ))
+            .with_children(|parent| {
+                parent.spawn_empty();
+            })
+            .id();
+        let x = |app: &App| app.world.get::<Transform>(pipe).unwrap().translation.x;
+        let score = |app: &App| app.world.resource::<Score>().0;
+
+        while x(&app) >= 0.0 {
+            assert_eq!(score(&app), 0);
+            app.update();
+        }
+        assert_eq!(score(&app), 1);
+
+        // Flies on behind the bird until it's recycled back out ahead
+        let mut recycled = false;
+        for _ in 0..1000 {
+            let before = x(&app);
+            app.update();
+            if x(&app) > before {
+                recycled = true;
+                break;
+            }
+            assert_eq!(score(&app), 1);
+        }
+        assert!(recycled);
+        assert!(x(&app) > 0.0);
+        assert!(!app.world.get::<Pipe>(pipe).unwrap().passed);
+        assert_eq!(score(&app), 1);
+
+        while x(&app) >= 0.0 {
+            app.update();
+        }
+        assert_eq!(score(&app), 2);
+        for _ in 0..10 {
+            app.update();
+        }
+        assert_eq!(score(&app), 2);
+    }
+
+    // A run as `main` starts it, with the systems that work on its pipes
+    fn playing_app(config: GameConfig) -> App {
+        init_window();
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<Image>()
+            .add_asset::<TextureAtlas>()
+            .insert_resource(config)
+            .add_plugin(theme::ThemePlugin)
+            .insert_resource(PipeRng::new(1, true))
+            .init_resource::<Score>()
+            .init_resource::<CameraOffset>()
+            .init_resource::<Comeback>()
+            .init_resource::<PipeLooks>()
+            .init_resource::<NextPipe>()
+            .init_resource::<HighScore>()
+            .init_resource::<RunTime>()
+            .init_resource::<SlowMotion>()
+            .add_state::<GameState>()
+            .add_system(spawn_run.in_schedule(OnEnter(GameState::Playing)))
+            .add_system(
+                reset_run
+                    .in_schedule(OnExit(GameState::Playing))
+                    .run_if(in_state(GameState::Playing)),
+            )
+            .add_systems(
+                (
+                    reuse_pipes.run_if(spawns_pipes),
+                    update_next_pipe.after(reuse_pipes).run_if(spawns_pipes),
+                    check_score.run_if(spawns_pipes),
+                    restyle_pipes.after(reuse_pipes).run_if(spawns_pipes),
+                    tint_pipes.after(reuse_pipes).run_if(spawns_pipes),
+                )
+                    .in_set(OnUpdate(GameState::Playing)),
+            );
+        app
+    }
+
+    #[test]
+    fn runs_without_pipes_play_a_frame() {
+        let mut app = playing_app(GameConfig {
+            spawn_pipes: false,
+            ..default()
+        });
+        app.update();
+        app.update();
+        let world = &mut app.world;
+        assert_eq!(world.query::<&Player>().iter(world).count(), 1);
+        assert_eq!(world.query::<&Pipe>().iter(world).count(), 0);
+        assert_eq!(world.resource::<NextPipe>().0, None);
+    }
+
+    #[test]
+    fn every_game_mode_can_be_switched_to_and_played() {
+        let mut app = playing_app(GameConfig::default());
+        let settings = Settings::default();
+        let key = settings.key_bindings.game_mode;
+        app.init_resource::<Input<KeyCode>>()
+            .insert_resource(Language::load(lang::ENGLISH))
+            .insert_resource(settings.key_bindings.clone())
+            .insert_resource(settings)
+            .add_system(settings::switch_game_mode.before(settings::apply_settings))
+            .add_system(settings::apply_settings);
+        app.world.spawn((
+            Flash::bundle("", Handle::default(), Color::WHITE, Val::Auto),
+            settings::SettingsResetLabel,
+        ));
+        app.update();
+
+        // Around every mode and back to the first
+        for mode in GameMode::ALL
+            .iter()
+            .cycle()
+            .skip(1)
+            .take(GameMode::ALL.len())
+        {
+            app.world.resource_mut::<Input<KeyCode>>().press(key);
+            app.update();
+            let mut input = app.world.resource_mut::<Input<KeyCode>>();
+            input.release(key);
+            input.clear();
+            assert_eq!(app.world.resource::<Settings>().mode(), *mode);
+
+            // The restart into the new mode, and its first frame
+            app.update();
+            let world = &mut app.world;
+            assert_eq!(
+                world.query::<&Player>().iter(world).count(),
+                1,
+                "{:?}",
+                mode
+            );
+            let pipes = world.query::<&Pipe>().iter(world).count();
+            assert_eq!(pipes > 0, *mode != GameMode::Zen, "{:?}", mode);
+        }
+    }
+
+    #[test]
+    fn reuse_pipes_without_any_pipes_does_nothing() {
+        init_window();
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .add_plugin(AssetPlugin::default())
+            .add_asset::<Image>()
+            .init_resource::<GameConfig>()
+            .insert_resource(PipeRng::new(1, true))
+            .init_resource::<Score>()
+            .init_resource::<CameraOffset>()
+            .init_resource::<Comeback>()
+            .init_resource::<PipeLooks>()
+            .add_system(reuse_pipes);
+        app.world.spawn((Transform::default(), Player));
+        app.update();
+    }
+
+    #[test]
+    fn reachable_gap_y_clamps_to_climb_and_drop() {
+        let config = GameConfig::default();
+        // A second apart, the bird can climb `flap_speed` or fall half of gravity
+        let (spacing, speed) = (300.0, 300.0);
+        let climb = config.flap_speed * config.gap_reach_margin;
+        let drop = -config.gravity / 2.0 * config.gap_reach_margin;
+        let reach = |y| reachable_gap_y(&config, 0.0, y, spacing, speed);
+        assert_eq!(reach(climb / 2.0), climb / 2.0);
+        assert_eq!(reach(-drop / 2.0), -drop / 2.0);
+        assert_eq!(reach(climb + 100.0), climb);
+        assert_eq!(reach(-drop - 100.0), -drop);
+    }
+
+    #[test]
+    fn reachable_gap_y_survives_tuning_without_flap_or_speed() {
+        let config = GameConfig {
+            flap_speed: 0.0,
+            ..default()
+        };
+        // Standing pipes would give any drawn gap infinite time to reach
+        assert_eq!(reachable_gap_y(&config, 0.0, 120.0, 300.0, 0.0), 120.0);
+        // Without a flap there is no climbing at all
+        assert_eq!(reachable_gap_y(&config, 0.0, 120.0, 300.0, 300.0), 0.0);
+        assert_eq!(reachable_gap_y(&config, 0.0, -120.0, 300.0, 300.0), -120.0);
+    }
+
+    #[test]
+    fn reachable_gap_y_is_relative_to_the_previous_gap() {
+        let config = GameConfig::default();
+        let from_origin = reachable_gap_y(&config, 0.0, 10_000.0, 300.0, 300.0);
+        let from_above = reachable_gap_y(&config, 50.0, 10_000.0, 300.0, 300.0);
+        assert_eq!(from_above - from_origin, 50.0);
+    }
+
+    #[test]
+    fn gaps_are_drawn_clear_of_floor_and_ceiling() {
+        init_window();
+        let config = GameConfig::default();
+        let range = gap_y_range(&config, config.gap_size);
+        assert!(range.start() < range.end());
+        assert_eq!(
+            *range.start() - config.gap_size / 2.0,
+            floor_y(&config) + GAP_EDGE_MARGIN
+        );
+        assert_eq!(
+            *range.end() + config.gap_size / 2.0,
+            visible_bounds(&config).max.y - GAP_EDGE_MARGIN
+        );
+        // Too tall to fit, so it can only go in the middle
+        let range = gap_y_range(&config, 10_000.0);
+        assert_eq!(range.start(), range.end());
+    }
+
+    #[test]
+    fn ground_art_tops_out_at_the_death_line() {
+        init_window();
+        for ground_height in [10.0, 80.0, 200.0] {
+            let config = GameConfig {
+                ground_height,
+                ..default()
+            };
+            let rows = ground_row_ys(&config);
+            let top = rows[0] + GROUND_TILE_SIZE / 2.0;
+            let bottom = rows[rows.len() - 1] - GROUND_TILE_SIZE / 2.0;
+            assert_eq!(top, floor_y(&config));
+            assert!(bottom <= visible_bounds(&config).min.y);
+        }
+    }
+
+    #[test]
+    fn ground_height_moves_art_and_death_line_together() {
+        init_window();
+        let low = GameConfig {
+            ground_height: 80.0,
+            ..default()
+        };
+        let high = GameConfig {
+            ground_height: 120.0,
+            ..default()
+        };
+        assert_eq!(floor_y(&high) - floor_y(&low), 40.0);
+        assert_eq!(ground_row_ys(&high)[0] - ground_row_ys(&low)[0], 40.0);
+    }
 }
@@ -0,0 +1,78 @@
+use bevy::{asset::FileAssetIo, prelude::*};
+
+use crate::{settings::Settings, speed_progress, GameConfig, GameState, Movable, Pipe};
+
+const MUSIC_PATH: &str = "sounds/music.ogg";
+// Playback speed at full pipe speed, kept low so the track never turns into chipmunks
+const MAX_MUSIC_SPEED: f32 = 1.2;
+
+#[derive(Resource)]
+struct Music(Handle<AudioSink>);
+
+/// Loops the soundtrack, if one is shipped, optionally speeding it up as the pipes accelerate.
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_startup_system(start_music)
+            .add_system(reset_music_speed.in_schedule(OnEnter(GameState::Playing)))
+            .add_system(scale_music_speed.in_set(OnUpdate(GameState::Playing)))
+            .add_system(set_music_volume.run_if(resource_changed::<Settings>()));
+    }
+}
+
+fn start_music(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    audio: Res<Audio>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    settings: Res<Settings>,
+) {
+    // The soundtrack is optional, so don't log a load failure every launch without it
+    if !FileAssetIo::get_base_path()
+        .join("assets")
+        .join(MUSIC_PATH)
+        .exists()
+    {
+        info!("No music at {}, playing without it", MUSIC_PATH);
+        return;
+    }
+    let sink = audio.play_with_settings(
+        asset_server.load(MUSIC_PATH),
+        PlaybackSettings::LOOP.with_volume(settings.music_volume),
+    );
+    commands.insert_resource(Music(audio_sinks.get_handle(sink)));
+}
+
+fn set_music_volume(
+    settings: Res<Settings>,
+    music: Option<Res<Music>>,
+    audio_sinks: Res<Assets<AudioSink>>,
+) {
+    if let Some(sink) = music.and_then(|music| audio_sinks.get(&music.0)) {
+        sink.set_volume(settings.music_volume);
+    }
+}
+
+fn reset_music_speed(music: Option<Res<Music>>, audio_sinks: Res<Assets<AudioSink>>) {
+    if let Some(sink) = music.and_then(|music| audio_sinks.get(&music.0)) {
+        sink.set_speed(1.0);
+    }
+}
+
+fn scale_music_speed(
+    config: Res<GameConfig>,
+    music: Option<Res<Music>>,
+    audio_sinks: Res<Assets<AudioSink>>,
+    pipes: Query<&Movable, With<Pipe>>,
+) {
+    if !config.music_tempo_scaling {
+        return;
+    }
+    let Some(sink) = music.and_then(|music| audio_sinks.get(&music.0)) else {
+        return;
+    };
+    if let Some(pipe) = pipes.iter().next() {
+        sink.set_speed(1.0 + (MAX_MUSIC_SPEED - 1.0) * speed_progress(pipe));
+    }
+}
@@ -0,0 +1,211 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    layers,
+    performance::{Decoration, PerformanceMode, VisualBudget},
+    theme::Theme,
+    FlapEvent, GameConfig, GameState, Player, GRAVITY,
+};
+
+const PARTICLE_LIFETIME: f32 = 0.8;
+const PARTICLE_SIZE: f32 = 24.0;
+const PARTICLE_SPEED: f32 = 400.0;
+const FLAP_PARTICLE_LIFETIME: f32 = 0.3;
+const FLAP_PARTICLE_SPEED: f32 = 150.0;
+// Mashing flap still puffs at most this often, which also bounds how many puffs are alive at once
+const FLAP_PUFF_INTERVAL: f32 = 0.1;
+// How far below the bird's centre the puffs start
+const FLAP_PUFF_OFFSET: f32 = 20.0;
+
+#[derive(Component)]
+struct Particle {
+    velocity: Vec3,
+    life: Timer,
+}
+
+/// Every death and flap particle there will ever be, spawned hidden up front and reused by each
+/// burst, so repeated deaths and flaps never spawn or despawn entities.
+#[derive(Resource, Default)]
+pub struct ParticlePool {
+    particles: Vec<Entity>,
+    flap_particles: Vec<Entity>,
+    // Flap puffs take turns through their pool, the oldest puff being the next one reused
+    next_flap_particle: usize,
+}
+
+/// Bursts `GameConfig::death_particles` puffs out of the bird when it dies, and a few smaller
+/// ones below it on every flap.
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ParticlePool>()
+            .add_startup_system(fill_pool)
+            .add_system(burst.in_schedule(OnEnter(GameState::GameOver)))
+            .add_system(hide_particles.in_schedule(OnEnter(GameState::Playing)))
+            .add_system(puff.in_set(OnUpdate(GameState::Playing)))
+            .add_system(update_particles);
+    }
+}
+
+fn fill_pool(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut theme: ResMut<Theme>,
+    config: Res<GameConfig>,
+    mut pool: ResMut<ParticlePool>,
+) {
+    let texture = theme.image(&asset_server, "sprites/smoke.png");
+    let mut spawn = |count: usize, size: f32, lifetime: f32, z: f32| -> Vec<Entity> {
+        (0..count)
+            .map(|_| {
+                commands
+                    .spawn((
+                        SpriteBundle {
+                            texture: texture.clone(),
+                            sprite: Sprite {
+                                custom_size: Some(Vec2::splat(size)),
+                                ..default()
+                            },
+                            transform: Transform::from_xyz(0.0, 0.0, z),
+                            visibility: Visibility::Hidden,
+                            ..default()
+                        },
+                        Particle {
+                            velocity: Vec3::ZERO,
+                            life: Timer::from_seconds(lifetime, TimerMode::Once),
+                        },
+                        Decoration,
+                    ))
+                    .id()
+            })
+            .collect()
+    };
+    pool.particles = spawn(
+        config.death_particles,
+        PARTICLE_SIZE,
+        PARTICLE_LIFETIME,
+        layers::DEATH_PARTICLES,
+    );
+    let live_puffs = (FLAP_PARTICLE_LIFETIME / FLAP_PUFF_INTERVAL).ceil() as usize;
+    pool.flap_particles = spawn(
+        config.flap_particles * live_puffs,
+        config.flap_particle_size,
+        FLAP_PARTICLE_LIFETIME,
+        layers::FLAP_PARTICLES,
+    );
+}
+
+fn burst(
+    config: Res<GameConfig>,
+    performance: Res<PerformanceMode>,
+    mut budget: ResMut<VisualBudget>,
+    pool: Res<ParticlePool>,
+    player: Query<&Transform, (With<Player>, Without<Particle>)>,
+    mut particles: Query<(&mut Transform, &mut Visibility, &mut Particle)>,
+) {
+    if config.reduce_motion || performance.active {
+        return;
+    }
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    // Purely cosmetic, so this stays off the seeded pipe RNG
+    let mut rng = rand::thread_rng();
+    let count = scaled_count(config.death_particles, config.effects_intensity());
+    let mut iter = particles.iter_many_mut(pool.particles.iter().take(count));
+    while let Some((mut transform, mut visibility, mut particle)) = iter.fetch_next() {
+        if !budget.try_spend() {
+            break;
+        }
+        let angle = rng.gen_range(0.0..std::f32::consts::TAU);
+        let speed = rng.gen_range(0.3..1.0) * PARTICLE_SPEED;
+        transform.translation.x = player.translation.x;
+        transform.translation.y = player.translation.y;
+        particle.velocity = Vec3::new(angle.cos(), angle.sin(), 0.0) * speed;
+        particle.life.reset();
+        *visibility = Visibility::Visible;
+    }
+}
+
+// At least one particle as long as the effects are on at all
+fn scaled_count(count: usize, intensity: f32) -> usize {
+    if count == 0 || intensity <= 0.0 {
+        return 0;
+    }
+    ((count as f32 * intensity).round() as usize).max(1)
+}
+
+#[allow(clippy::too_many_arguments)]
+fn puff(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    performance: Res<PerformanceMode>,
+    mut budget: ResMut<VisualBudget>,
+    mut flaps: EventReader<FlapEvent>,
+    mut since_puff: Local<f32>,
+    mut pool: ResMut<ParticlePool>,
+    player: Query<&Transform, (With<Player>, Without<Particle>)>,
+    mut particles: Query<(&mut Transform, &mut Visibility, &mut Particle)>,
+) {
+    *since_puff += time.delta_seconds();
+    // Read every frame, so flaps from before a skipped one don't pile up
+    if flaps.iter().count() == 0
+        || *since_puff < FLAP_PUFF_INTERVAL
+        || config.reduce_motion
+        || performance.active
+        || pool.flap_particles.is_empty()
+    {
+        return;
+    }
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    *since_puff = 0.0;
+    let mut rng = rand::thread_rng();
+    for _ in 0..scaled_count(config.flap_particles, config.effects_intensity()) {
+        if !budget.try_spend() {
+            break;
+        }
+        let entity = pool.flap_particles[pool.next_flap_particle];
+        pool.next_flap_particle = (pool.next_flap_particle + 1) % pool.flap_particles.len();
+        let Ok((mut transform, mut visibility, mut particle)) = particles.get_mut(entity) else {
+            continue;
+        };
+        // Fanning out downward, away from the push of the flap
+        let angle = -std::f32::consts::FRAC_PI_2 + rng.gen_range(-0.8..0.8);
+        let speed = rng.gen_range(0.3..1.0) * FLAP_PARTICLE_SPEED;
+        transform.translation.x = player.translation.x;
+        transform.translation.y = player.translation.y - FLAP_PUFF_OFFSET;
+        particle.velocity = Vec3::new(angle.cos(), angle.sin(), 0.0) * speed;
+        particle.life.reset();
+        *visibility = Visibility::Visible;
+    }
+}
+
+fn hide_particles(mut particles: Query<&mut Visibility, With<Particle>>) {
+    for mut visibility in &mut particles {
+        *visibility = Visibility::Hidden;
+    }
+}
+
+fn update_particles(
+    time: Res<Time>,
+    mut particles: Query<(&mut Transform, &mut Visibility, &mut Sprite, &mut Particle)>,
+) {
+    let dt = time.delta_seconds();
+    for (mut transform, mut visibility, mut sprite, mut particle) in &mut particles {
+        if *visibility == Visibility::Hidden {
+            continue;
+        }
+        // A fraction of the bird's gravity, so the puffs drift down rather than drop
+        particle.velocity.y += GRAVITY * 0.25 * dt;
+        transform.translation += particle.velocity * dt;
+        particle.life.tick(time.delta());
+        sprite.color.set_a(particle.life.percent_left());
+        if particle.life.finished() {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
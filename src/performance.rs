@@ -0,0 +1,172 @@
+use bevy::{
+    diagnostic::{Diagnostics, FrameTimeDiagnosticsPlugin},
+    prelude::*,
+};
+
+use crate::GameConfig;
+
+// Engages below the first rate and releases above the second, each only after holding for a
+// while, so a frame rate hovering around one threshold doesn't toggle it every few frames
+const ENGAGE_FPS: f64 = 45.0;
+const ENGAGE_AFTER: f32 = 3.0;
+const RELEASE_FPS: f64 = 55.0;
+const RELEASE_AFTER: f32 = 10.0;
+// Share of the visual budget left while performance mode is on
+const PERFORMANCE_BUDGET_DIVISOR: usize = 4;
+
+/// While active, purely decorative effects such as squash-and-stretch and death particles
+/// are skipped to keep the frame rate up.
+#[derive(Resource, Default)]
+pub struct PerformanceMode {
+    pub active: bool,
+    // How long the frame rate has been on the other side of the current mode's threshold
+    pending: f32,
+}
+
+/// Decorative sprite that counts against the `VisualBudget` while it's visible.
+#[derive(Component)]
+pub struct Decoration;
+
+/// Caps how many decorations show at once, so effects piling up on a long run can't drag the
+/// frame rate down. Spawners ask with `try_spend` before showing one and skip it if refused.
+#[derive(Resource, Default)]
+pub struct VisualBudget {
+    used: usize,
+    cap: usize,
+}
+
+impl VisualBudget {
+    pub fn try_spend(&mut self) -> bool {
+        if self.used >= self.cap {
+            return false;
+        }
+        self.used += 1;
+        true
+    }
+}
+
+/// Switches `PerformanceMode` on when `GameConfig::performance_mode` asks for it, or on
+/// sustained low frame rates when `auto_performance_mode` is set.
+pub struct PerformancePlugin;
+
+impl Plugin for PerformancePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(FrameTimeDiagnosticsPlugin)
+            .init_resource::<PerformanceMode>()
+            .init_resource::<VisualBudget>()
+            .add_system(detect_low_fps)
+            .add_system(count_decorations.in_base_set(CoreSet::PreUpdate));
+    }
+}
+
+// Recounted from what's actually visible every frame, so nothing has to hand its share back
+fn count_decorations(
+    config: Res<GameConfig>,
+    mode: Res<PerformanceMode>,
+    decorations: Query<&Visibility, With<Decoration>>,
+    mut budget: ResMut<VisualBudget>,
+) {
+    budget.cap = if mode.active {
+        config.visual_budget / PERFORMANCE_BUDGET_DIVISOR
+    } else {
+        config.visual_budget
+    };
+    budget.used = decorations
+        .iter()
+        .filter(|visibility| **visibility != Visibility::Hidden)
+        .count();
+}
+
+fn detect_low_fps(
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    diagnostics: Res<Diagnostics>,
+    mut mode: ResMut<PerformanceMode>,
+) {
+    if config.performance_mode {
+        if !mode.active {
+            info!("Performance mode on");
+            mode.active = true;
+        }
+        return;
+    }
+    if !config.auto_performance_mode {
+        if mode.active {
+            info!("Performance mode off");
+            mode.active = false;
+        }
+        return;
+    }
+
+    let Some(fps) = diagnostics
+        .get(FrameTimeDiagnosticsPlugin::FPS)
+        .and_then(|fps| fps.smoothed())
+    else {
+        return;
+    };
+    let (wants_switch, hold) = if mode.active {
+        (fps > RELEASE_FPS, RELEASE_AFTER)
+    } else {
+        (fps < ENGAGE_FPS, ENGAGE_AFTER)
+    };
+    if !wants_switch {
+        mode.pending = 0.0;
+        return;
+    }
+    mode.pending += time.delta_seconds();
+    if mode.pending >= hold {
+        mode.pending = 0.0;
+        mode.active = !mode.active;
+        info!(
+            "Performance mode {} at {:.0} FPS",
+            if mode.active { "on" } else { "off" },
+            fps
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn budget_refuses_past_cap() {
+        let mut budget = VisualBudget { used: 0, cap: 3 };
+        let spent: Vec<bool> = (0..6).map(|_| budget.try_spend()).collect();
+        assert_eq!(spent, [true, true, true, false, false, false]);
+        assert_eq!(budget.used, 3);
+    }
+
+    #[test]
+    fn counts_only_visible_decorations() {
+        let mut app = App::new();
+        app.add_plugins(MinimalPlugins)
+            .insert_resource(GameConfig {
+                visual_budget: 8,
+                ..default()
+            })
+            .init_resource::<PerformanceMode>()
+            .init_resource::<VisualBudget>()
+            .add_system(count_decorations);
+        for visibility in [
+            Visibility::Visible,
+            Visibility::Inherited,
+            Visibility::Hidden,
+            Visibility::Hidden,
+        ] {
+            app.world.spawn((visibility, Decoration));
+        }
+        // Not a decoration, so never counted
+        app.world.spawn(Visibility::Visible);
+        app.update();
+        assert_eq!(app.world.resource::<VisualBudget>().used, 2);
+        assert_eq!(app.world.resource::<VisualBudget>().cap, 8);
+
+        app.world.resource_mut::<PerformanceMode>().active = true;
+        app.update();
+        assert_eq!(
+            app.world.resource::<VisualBudget>().cap,
+            8 / PERFORMANCE_BUDGET_DIVISOR
+        );
+    }
+}
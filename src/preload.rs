@@ -0,0 +1,100 @@
+use bevy::{asset::LoadState, prelude::*};
+
+use crate::{GameConfig, Theme};
+
+// Sprites each run spawns its entities with. Everything else is loaded once at startup and kept
+const RUN_SPRITES: [&str; 4] = [
+    "sprites/bird.png",
+    "sprites/pipe.png",
+    "sprites/pipe_piece.png",
+    "sprites/smoke.png",
+];
+const OPTIONAL_RUN_SPRITES: [&str; 1] = ["sprites/pipe_cap.png"];
+
+/// Assets gameplay needs at a moment's notice, loaded up front and held for the whole session.
+/// Otherwise the first flap, and every restart after the run's sprites were dropped, could wait
+/// on a load.
+#[derive(Resource, Default)]
+pub struct Preload {
+    pending: Vec<(String, Handle<Image>)>,
+    // Kept so nothing preloaded is unloaded between runs
+    loaded: Vec<Handle<Image>>,
+}
+
+impl Preload {
+    pub fn ready(&self) -> bool {
+        self.pending.is_empty()
+    }
+}
+
+/// Preloads with `GameConfig::preload_assets`, and holds the bird's first flap until everything
+/// is in.
+pub struct PreloadPlugin;
+
+impl Plugin for PreloadPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Preload>()
+            .add_startup_system(start)
+            .add_system(track.run_if(|preload: Res<Preload>| !preload.ready()));
+    }
+}
+
+/// Run condition for systems that shouldn't act before the preload is done.
+pub fn ready(preload: Res<Preload>) -> bool {
+    preload.ready()
+}
+
+fn start(
+    config: Res<GameConfig>,
+    asset_server: Res<AssetServer>,
+    mut theme: ResMut<Theme>,
+    mut preload: ResMut<Preload>,
+) {
+    if !config.preload_assets {
+        return;
+    }
+    let mut sprites: Vec<(String, Handle<Image>)> = RUN_SPRITES
+        .iter()
+        .map(|path| (path.to_string(), theme.image(&asset_server, path)))
+        .collect();
+    sprites.extend(
+        OPTIONAL_RUN_SPRITES.iter().filter_map(|path| {
+            Some((path.to_string(), theme.optional_image(&asset_server, path)?))
+        }),
+    );
+    for name in &config.pipe_variants {
+        for path in [
+            format!("sprites/pipe_{}.png", name),
+            format!("sprites/pipe_piece_{}.png", name),
+        ] {
+            if let Some(handle) = theme.optional_image(&asset_server, &path) {
+                sprites.push((path, handle));
+            }
+        }
+    }
+    if let Some(animation) = &config.pipe_start_animation {
+        let handle = asset_server.load(animation.texture.as_str());
+        sprites.push((animation.texture.clone(), handle));
+    }
+    preload.pending = sprites;
+}
+
+// By the images themselves, since the theme's placeholder never went through the asset server
+fn track(asset_server: Res<AssetServer>, images: Res<Assets<Image>>, mut preload: ResMut<Preload>) {
+    let Preload { pending, loaded } = &mut *preload;
+    pending.retain(|(path, handle)| {
+        if images.contains(handle) {
+            loaded.push(handle.clone());
+            return false;
+        }
+        // Not worth holding the game up for, the sprite just stays missing
+        if asset_server.get_load_state(handle) == LoadState::Failed {
+            error!("Failed to preload {}", path);
+            return false;
+        }
+        true
+    });
+    if pending.is_empty() {
+        info!("Preloaded {} assets", loaded.len());
+    }
+}
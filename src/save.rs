@@ -0,0 +1,45 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    path::{Path, PathBuf},
+};
+
+/// Replaces the file at `path` with `contents` in one step. The data goes to a temporary file
+/// next to it first and is renamed over the target, so a crash mid-save leaves either the old
+/// file or the new one, never a truncated mix. On failure the old file is left untouched.
+pub fn write_atomic(path: impl AsRef<Path>, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let path = path.as_ref();
+    let temp = temp_path(path);
+    let result = write_synced(&temp, contents.as_ref()).and_then(|()| fs::rename(&temp, path));
+    if result.is_err() {
+        // Nothing useful left in it, and a stale one would only confuse
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Adds `line` to the end of the file at `path`, creating it with `header` first if it doesn't
+/// exist or is empty. Each goes out in a single write to a file opened for appending, so a crash
+/// can cut off at most the line being written.
+pub fn append_line(path: impl AsRef<Path>, header: &str, line: &str) -> io::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    if file.metadata()?.len() == 0 {
+        file.write_all(format!("{}\n", header).as_bytes())?;
+    }
+    file.write_all(format!("{}\n", line).as_bytes())?;
+    file.sync_all()
+}
+
+// Same directory as the target, since a rename can't cross file systems
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+fn write_synced(path: &Path, contents: &[u8]) -> io::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(contents)?;
+    // Make sure the data is on disk before the rename makes it the real file
+    file.sync_all()
+}
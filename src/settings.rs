@@ -0,0 +1,258 @@
+use bevy::{
+    prelude::*,
+    window::{PrimaryWindow, WindowMode},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config::{GameConfig, GameMode, KeyBindings, MsaaSetting},
+    daily::DailyChallenge,
+    lang::{self, Language},
+    save, Flash, GameState, FLASH_DURATION,
+};
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Player preferences that survive restarts, stored as JSON next to the other save files.
+/// Missing fields fall back to their defaults, so older files keep loading.
+#[derive(Resource, Serialize, Deserialize, Clone, PartialEq, Debug)]
+#[serde(default)]
+pub struct Settings {
+    pub music_volume: f32,
+    pub fullscreen: bool,
+    pub msaa: MsaaSetting,
+    pub reduce_motion: bool,
+    pub effects_intensity: f32,
+    pub assist_mode: bool,
+    pub ground_assist: bool,
+    pub hud_minimal: bool,
+    pub progress_bar: bool,
+    pub ghost_pipe: bool,
+    pub camera_lookahead: bool,
+    pub run_log: bool,
+    /// The mode last played, started in again on the next launch.
+    pub game_mode: GameMode,
+    /// Mode for this session only, such as `--zen`'s, in place of `game_mode`. Never saved, and
+    /// dropped once the player switches modes.
+    #[serde(skip)]
+    pub launch_mode: Option<GameMode>,
+    /// Code of a language file in `assets/lang`.
+    pub language: String,
+    pub key_bindings: KeyBindings,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        let config = GameConfig::default();
+        Self {
+            music_volume: 1.0,
+            fullscreen: false,
+            msaa: config.msaa,
+            reduce_motion: config.reduce_motion,
+            effects_intensity: config.effects_intensity,
+            assist_mode: config.assist_mode,
+            ground_assist: config.ground_assist,
+            hud_minimal: config.hud_minimal,
+            progress_bar: config.progress_bar,
+            ghost_pipe: config.ghost_pipe,
+            camera_lookahead: config.camera_lookahead,
+            run_log: config.run_log,
+            game_mode: GameMode::default(),
+            launch_mode: None,
+            language: lang::ENGLISH.to_string(),
+            key_bindings: KeyBindings::default(),
+        }
+    }
+}
+
+impl Settings {
+    // Loaded before the app and its logger exist, so problems go straight to stderr
+    pub fn load() -> Self {
+        let Ok(contents) = std::fs::read_to_string(SETTINGS_FILE) else {
+            return Self::default();
+        };
+        serde_json::from_str(&contents).unwrap_or_else(|err| {
+            eprintln!("Ignoring unreadable settings at {}: {}", SETTINGS_FILE, err);
+            Self::default()
+        })
+    }
+
+    fn save(&self) -> std::io::Result<()> {
+        save::write_atomic(SETTINGS_FILE, serde_json::to_string_pretty(self)?)
+    }
+
+    /// The mode runs are played in right now.
+    pub fn mode(&self) -> GameMode {
+        self.launch_mode.unwrap_or(self.game_mode)
+    }
+
+    /// Copies the stored preferences over the matching config fields.
+    pub fn apply(&self, config: &mut GameConfig) {
+        config.msaa = self.msaa;
+        // No effects at all is reduce motion by another name
+        config.reduce_motion = self.reduce_motion || self.effects_intensity <= 0.0;
+        config.effects_intensity = self.effects_intensity;
+        config.assist_mode = self.assist_mode;
+        config.ground_assist = self.ground_assist;
+        config.hud_minimal = self.hud_minimal;
+        config.progress_bar = self.progress_bar;
+        config.ghost_pipe = self.ghost_pipe;
+        config.camera_lookahead = self.camera_lookahead;
+        config.run_log = self.run_log;
+        self.mode().apply(config);
+    }
+}
+
+#[derive(Component)]
+pub struct SettingsResetLabel;
+
+/// Asks for a second press of the reset key before anything is lost.
+pub fn reset_settings(
+    time: Res<Time>,
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    language: Res<Language>,
+    mut confirm: Local<Timer>,
+    mut settings: ResMut<Settings>,
+    mut label: Query<(&mut Text, &mut Flash, &mut Visibility), With<SettingsResetLabel>>,
+) {
+    // Still running while waiting for the confirmation
+    let armed = !confirm.tick(time.delta()).finished();
+    if !keyboard_input.just_pressed(bindings.reset_settings) {
+        return;
+    }
+    let (mut text, mut flash, mut visibility) = label.single_mut();
+    if armed {
+        *confirm = Timer::default();
+        *settings = Settings::default();
+        text.sections[0].value = language.t("settings.reset").to_string();
+    } else {
+        *confirm = Timer::from_seconds(FLASH_DURATION, TimerMode::Once);
+        text.sections[0].value = language.format(
+            "settings.confirm_reset",
+            &[&format!("{:?}", bindings.reset_settings)],
+        );
+    }
+    flash.0.reset();
+    *visibility = Visibility::Visible;
+}
+
+/// Moves on to the next language in `assets/lang`, named in that language as confirmation.
+pub fn cycle_language(
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    mut settings: ResMut<Settings>,
+    mut label: Query<(&mut Text, &mut Flash, &mut Visibility), With<SettingsResetLabel>>,
+) {
+    if !keyboard_input.just_pressed(bindings.language) {
+        return;
+    }
+    let codes = lang::available();
+    let next = codes
+        .iter()
+        .position(|code| *code == settings.language)
+        .map_or(0, |i| (i + 1) % codes.len());
+    settings.language = codes[next].clone();
+
+    let language = Language::load(&settings.language);
+    let (mut text, mut flash, mut visibility) = label.single_mut();
+    text.sections[0].value = language.format("settings.language", &[&language.t("language.name")]);
+    flash.0.reset();
+    *visibility = Visibility::Visible;
+}
+
+/// Turns all effects down a quarter at a time, then back up to full.
+pub fn cycle_effects_intensity(
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    language: Res<Language>,
+    mut settings: ResMut<Settings>,
+    mut label: Query<(&mut Text, &mut Flash, &mut Visibility), With<SettingsResetLabel>>,
+) {
+    if !keyboard_input.just_pressed(bindings.effects_intensity) {
+        return;
+    }
+    // Rounded to the steps, in case the file held something in between
+    let quarters = (settings.effects_intensity.clamp(0.0, 1.0) * 4.0).round();
+    settings.effects_intensity = if quarters <= 0.0 {
+        1.0
+    } else {
+        (quarters - 1.0) / 4.0
+    };
+
+    let (mut text, mut flash, mut visibility) = label.single_mut();
+    let percent = (settings.effects_intensity * 100.0).round();
+    text.sections[0].value = language.format("settings.effects_intensity", &[&percent]);
+    flash.0.reset();
+    *visibility = Visibility::Visible;
+}
+
+/// Moves on to the next game mode, which `apply_settings` starts a fresh run in.
+pub fn switch_game_mode(
+    keyboard_input: Res<Input<KeyCode>>,
+    bindings: Res<KeyBindings>,
+    language: Res<Language>,
+    daily: Option<Res<DailyChallenge>>,
+    mut settings: ResMut<Settings>,
+    mut label: Query<(&mut Text, &mut Flash, &mut Visibility), With<SettingsResetLabel>>,
+) {
+    // Everyone plays the daily layout the same way
+    if daily.is_some() || !keyboard_input.just_pressed(bindings.game_mode) {
+        return;
+    }
+    settings.game_mode = settings.mode().next();
+    settings.launch_mode = None;
+
+    let (mut text, mut flash, mut visibility) = label.single_mut();
+    text.sections[0].value = language.format(
+        "settings.game_mode",
+        &[&language.t(settings.game_mode.name_key())],
+    );
+    flash.0.reset();
+    *visibility = Visibility::Visible;
+}
+
+/// Pushes changed settings into everything that was set up from them at startup. A different
+/// game mode, switched to or reset to, starts a fresh run in it from any state; mid-run that
+/// restarts the current state in place, see `reset_run`.
+pub fn apply_settings(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    mut config: ResMut<GameConfig>,
+    mut bindings: ResMut<KeyBindings>,
+    language: Res<Language>,
+    mut game_state: ResMut<NextState<GameState>>,
+    mut window: Query<&mut Window, With<PrimaryWindow>>,
+) {
+    if !settings.is_changed() || settings.is_added() {
+        return;
+    }
+    let mode_flags = |config: &GameConfig| (config.spawn_pipes, config.practice);
+    let before = mode_flags(&config);
+    settings.apply(&mut config);
+    if mode_flags(&config) != before {
+        game_state.set(GameState::Playing);
+    }
+    *bindings = settings.key_bindings.clone();
+    if language.code() != settings.language {
+        commands.insert_resource(Language::load(&settings.language));
+    }
+    commands.insert_resource(Msaa::from(settings.msaa));
+    if let Ok(mut window) = window.get_single_mut() {
+        window.mode = if settings.fullscreen {
+            WindowMode::SizedFullscreen
+        } else {
+            WindowMode::Windowed
+        };
+    }
+}
+
+pub fn save_settings(settings: Res<Settings>) {
+    // Loading counts as a change, but there's nothing new to write yet
+    if !settings.is_changed() || settings.is_added() {
+        return;
+    }
+    if let Err(err) = settings.save() {
+        error!("Failed to save settings to {}: {}", SETTINGS_FILE, err);
+    }
+}
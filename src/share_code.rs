@@ -0,0 +1,87 @@
+use std::{fmt, str::FromStr};
+
+// Seed and score, followed by a checksum byte to catch typos
+const CODE_BYTES: usize = 8 + 4 + 1;
+
+/// A short code identifying a run: the seed the pipe layout was generated from and the final score.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub struct ShareCode {
+    pub seed: u64,
+    pub score: u32,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ShareCodeError {
+    Length(usize),
+    NotHex,
+    Checksum,
+}
+
+impl fmt::Display for ShareCodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShareCodeError::Length(length) => {
+                write!(f, "expected {} characters, got {}", CODE_BYTES * 2, length)
+            }
+            ShareCodeError::NotHex => write!(f, "only 0-9 and A-F are allowed"),
+            ShareCodeError::Checksum => write!(f, "the code is mistyped"),
+        }
+    }
+}
+
+impl std::error::Error for ShareCodeError {}
+
+fn checksum(bytes: &[u8]) -> u8 {
+    bytes
+        .iter()
+        .fold(0xA5, |acc: u8, byte| acc.rotate_left(1) ^ byte)
+}
+
+impl fmt::Display for ShareCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut bytes = Vec::with_capacity(CODE_BYTES);
+        bytes.extend_from_slice(&self.seed.to_be_bytes());
+        bytes.extend_from_slice(&self.score.to_be_bytes());
+        bytes.push(checksum(&bytes));
+        for byte in bytes {
+            write!(f, "{:02X}", byte)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for ShareCode {
+    type Err = ShareCodeError;
+
+    fn from_str(code: &str) -> Result<Self, Self::Err> {
+        // Be lenient with whatever the code was pasted with
+        let digits: Vec<char> = code
+            .chars()
+            .filter(|c| !c.is_whitespace() && *c != '-')
+            .collect();
+        if digits.len() != CODE_BYTES * 2 {
+            return Err(ShareCodeError::Length(digits.len()));
+        }
+
+        let bytes = digits
+            .chunks(2)
+            .map(|pair| {
+                let high = pair[0].to_digit(16)?;
+                let low = pair[1].to_digit(16)?;
+                Some((high * 16 + low) as u8)
+            })
+            .collect::<Option<Vec<u8>>>()
+            .ok_or(ShareCodeError::NotHex)?;
+
+        let (payload, check) = bytes.split_at(CODE_BYTES - 1);
+        if checksum(payload) != check[0] {
+            return Err(ShareCodeError::Checksum);
+        }
+
+        let (seed, score) = payload.split_at(8);
+        Ok(Self {
+            seed: u64::from_be_bytes(seed.try_into().unwrap()),
+            score: u32::from_be_bytes(score.try_into().unwrap()),
+        })
+    }
+}
@@ -0,0 +1,95 @@
+use bevy::{
+    prelude::*,
+    reflect::TypeUuid,
+    render::render_resource::{AsBindGroup, ShaderRef},
+    sprite::{Material2d, Material2dPlugin, MaterialMesh2dBundle},
+};
+
+use crate::{layers, visible_size, CameraOffset, GameConfig, TimeScale};
+
+/// Scrolling gradient and clouds drawn by `shaders/sky.wgsl`, in front of the static
+/// background. Should the shader fail to build, nothing is drawn and the sprite shows through.
+#[derive(AsBindGroup, TypeUuid, Debug, Clone)]
+#[uuid = "5b1c04d2-8f36-4f0e-a9d7-3c2e61b7f4a8"]
+struct SkyMaterial {
+    #[uniform(0)]
+    top_color: Color,
+    #[uniform(0)]
+    bottom_color: Color,
+    #[uniform(0)]
+    cloud_color: Color,
+    // Horizontal cloud offset, in widths of the sky
+    #[uniform(0)]
+    scroll: f32,
+}
+
+impl Material2d for SkyMaterial {
+    fn fragment_shader() -> ShaderRef {
+        "shaders/sky.wgsl".into()
+    }
+}
+
+#[derive(Component)]
+struct Sky;
+
+pub struct SkyPlugin;
+
+impl Plugin for SkyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugin(Material2dPlugin::<SkyMaterial>::default())
+            .add_startup_system(spawn_sky)
+            .add_system(update_sky);
+    }
+}
+
+fn spawn_sky(
+    mut commands: Commands,
+    config: Res<GameConfig>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<SkyMaterial>>,
+) {
+    if !config.sky_shader {
+        return;
+    }
+    commands.spawn((
+        MaterialMesh2dBundle {
+            mesh: meshes
+                .add(shape::Quad::new(visible_size(&config)).into())
+                .into(),
+            material: materials.add(SkyMaterial {
+                top_color: config.sky_top_color,
+                bottom_color: config.sky_bottom_color,
+                cloud_color: config.sky_cloud_color,
+                scroll: 0.0,
+            }),
+            transform: Transform::from_xyz(0.0, 0.0, layers::SKY),
+            ..default()
+        },
+        Sky,
+    ));
+}
+
+fn update_sky(
+    time: Res<Time>,
+    time_scale: Res<TimeScale>,
+    config: Res<GameConfig>,
+    camera_offset: Res<CameraOffset>,
+    mut materials: ResMut<Assets<SkyMaterial>>,
+    mut last_camera_x: Local<f32>,
+    mut sky: Query<(&Handle<SkyMaterial>, &mut Transform), With<Sky>>,
+) {
+    let Ok((handle, mut transform)) = sky.get_single_mut() else {
+        return;
+    };
+    // Always filling the view, whatever the camera does
+    transform.translation.x = camera_offset.total().x;
+    let camera_delta = camera_offset.total().x - *last_camera_x;
+    *last_camera_x = camera_offset.total().x;
+    if let Some(material) = materials.get_mut(handle) {
+        let dt = time.delta_seconds() * time_scale.0;
+        // The quad moves with the camera, so the clouds show their share of its movement
+        // by scrolling
+        let drift = config.sky_scroll_speed * dt + camera_delta * config.sky_parallax;
+        material.scroll += drift / visible_size(&config).x;
+    }
+}
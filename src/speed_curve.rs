@@ -0,0 +1,100 @@
+use std::{fmt, str::FromStr};
+
+/// What a speed curve's keyframes are positioned by.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum CurveInput {
+    /// Gameplay seconds since the run started.
+    Time,
+    Score,
+}
+
+/// Pipe speed as a piecewise linear function of time or score, held flat before the first
+/// keyframe and after the last. Written as the input followed by `at:speed` pairs, like
+/// `time 0:100 30:400 60:1000`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct SpeedCurve {
+    pub input: CurveInput,
+    // Sorted by position, checked on parse
+    keyframes: Vec<(f32, f32)>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum SpeedCurveError {
+    UnknownInput(String),
+    Empty,
+    Keyframe(String),
+    NotIncreasing(f32),
+    Speed(f32),
+}
+
+impl fmt::Display for SpeedCurveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SpeedCurveError::UnknownInput(input) => {
+                write!(f, "expected \"time\" or \"score\", got {:?}", input)
+            }
+            SpeedCurveError::Empty => write!(f, "no keyframes given"),
+            SpeedCurveError::Keyframe(keyframe) => {
+                write!(f, "{:?} is not an \"at:speed\" pair", keyframe)
+            }
+            SpeedCurveError::NotIncreasing(at) => {
+                write!(f, "keyframe at {} doesn't come after the one before it", at)
+            }
+            SpeedCurveError::Speed(speed) => write!(f, "speed {} must be positive", speed),
+        }
+    }
+}
+
+impl std::error::Error for SpeedCurveError {}
+
+impl SpeedCurve {
+    /// Speed at `at`, in pixels per second.
+    pub fn sample(&self, at: f32) -> f32 {
+        let after = self.keyframes.partition_point(|&(x, _)| x <= at);
+        match (after.checked_sub(1), self.keyframes.get(after)) {
+            (Some(before), Some(&(x1, speed1))) => {
+                let (x0, speed0) = self.keyframes[before];
+                speed0 + (speed1 - speed0) * (at - x0) / (x1 - x0)
+            }
+            (Some(before), None) => self.keyframes[before].1,
+            (None, _) => self.keyframes[0].1,
+        }
+    }
+}
+
+impl FromStr for SpeedCurve {
+    type Err = SpeedCurveError;
+
+    fn from_str(curve: &str) -> Result<Self, Self::Err> {
+        let mut words = curve.split_whitespace();
+        let input = match words.next() {
+            Some("time") => CurveInput::Time,
+            Some("score") => CurveInput::Score,
+            Some(other) => return Err(SpeedCurveError::UnknownInput(other.to_string())),
+            None => return Err(SpeedCurveError::Empty),
+        };
+
+        let mut keyframes: Vec<(f32, f32)> = Vec::new();
+        for word in words {
+            let (at, speed) = word
+                .split_once(':')
+                .and_then(|(at, speed)| Some((at.parse().ok()?, speed.parse().ok()?)))
+                .filter(|(at, speed): &(f32, f32)| at.is_finite() && speed.is_finite())
+                .ok_or_else(|| SpeedCurveError::Keyframe(word.to_string()))?;
+            if keyframes
+                .last()
+                .is_some_and(|&(previous, _)| at <= previous)
+            {
+                return Err(SpeedCurveError::NotIncreasing(at));
+            }
+            if speed <= 0.0 {
+                return Err(SpeedCurveError::Speed(speed));
+            }
+            keyframes.push((at, speed));
+        }
+        if keyframes.is_empty() {
+            return Err(SpeedCurveError::Empty);
+        }
+        Ok(Self { input, keyframes })
+    }
+}
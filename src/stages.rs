@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+
+use crate::{
+    config::StageOverrides, spawn_run, Background, Flash, GameConfig, GameState, HudRoot, Language,
+    Score,
+};
+
+/// Which stage the current run is in, counted from 0, and what the config was before the first
+/// stage changed it.
+#[derive(Resource, Default)]
+pub struct Stage {
+    pub index: usize,
+    base: Option<StageOverrides>,
+}
+
+#[derive(Component)]
+struct StageBanner;
+
+/// Moves runs through `GameConfig::stages` every `GameConfig::stage_size` pipes, and puts the
+/// config back for the next run.
+pub struct StagesPlugin;
+
+impl Plugin for StagesPlugin {
+    fn build(&self, app: &mut App) {
+        // After the main startup, so the HUD root exists
+        app.init_resource::<Stage>()
+            .add_startup_system(spawn_banner.in_base_set(StartupSet::PostStartup))
+            .add_system(
+                reset
+                    .before(spawn_run)
+                    .in_schedule(OnEnter(GameState::Playing)),
+            )
+            .add_system(
+                advance
+                    .in_set(OnUpdate(GameState::Playing))
+                    .run_if(resource_changed::<Score>())
+                    .run_if(|config: Res<GameConfig>| config.stage_size > 0),
+            )
+            .add_system(tint_background.run_if(resource_changed::<GameConfig>()));
+    }
+}
+
+fn spawn_banner(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    hud: Query<Entity, With<HudRoot>>,
+) {
+    let font = asset_server.load("fonts/FiraSans-Bold.ttf");
+    commands
+        .spawn((
+            Flash::bundle("", font, Color::WHITE, Val::Percent(25.)),
+            StageBanner,
+        ))
+        .set_parent(hud.single());
+}
+
+fn reset(mut config: ResMut<GameConfig>, mut stage: ResMut<Stage>) {
+    if let Some(base) = stage.base.take() {
+        base.apply(&mut config);
+    }
+    stage.index = 0;
+}
+
+fn advance(
+    score: Res<Score>,
+    language: Res<Language>,
+    mut config: ResMut<GameConfig>,
+    mut stage: ResMut<Stage>,
+    mut banner: Query<(&mut Text, &mut Flash, &mut Visibility), With<StageBanner>>,
+) {
+    let reached = ((score.0 / config.stage_size) as usize).min(config.stages.len());
+    if reached == stage.index {
+        return;
+    }
+    if stage.base.is_none() {
+        stage.base = Some(StageOverrides::capture(&config));
+    }
+    // Restoring a checkpoint can take the score back to an earlier stage, which is replayed
+    // from the start rather than undone
+    if reached < stage.index {
+        if let Some(base) = &stage.base {
+            base.apply(&mut config);
+        }
+        stage.index = 0;
+    }
+    // A checkpoint can start a run several stages in
+    while stage.index < reached {
+        let overrides = config.stages[stage.index].clone();
+        overrides.apply(&mut config);
+        stage.index += 1;
+    }
+
+    let (mut text, mut flash, mut visibility) = banner.single_mut();
+    text.sections[0].value = language.format("stage.banner", &[&(stage.index + 1)]);
+    flash.0.reset();
+    *visibility = Visibility::Visible;
+}
+
+fn tint_background(config: Res<GameConfig>, mut backgrounds: Query<&mut Sprite, With<Background>>) {
+    for mut sprite in &mut backgrounds {
+        sprite.color = config.background_tint;
+    }
+}
@@ -0,0 +1,86 @@
+use bevy::prelude::*;
+
+use crate::{
+    autopilot::AutopilotControl, counts_for_records, save, show_game_over, Comeback,
+    DailyChallenge, GameConfig, GameState, PipeRng, RunTime,
+};
+
+const SURVIVAL_RECORD_PATH: &str = "survival_record.txt";
+
+/// Longest time survived in one run, in gameplay seconds, kept beside the high score for players
+/// who play for time rather than pipes. Follows the same rules as the high score for which runs
+/// count, so a run can set both.
+#[derive(Resource)]
+pub struct SurvivalRecord {
+    best: f32,
+    // The run that just ended beat `best`
+    set_this_run: bool,
+}
+
+impl SurvivalRecord {
+    fn load() -> Self {
+        let best = std::fs::read_to_string(SURVIVAL_RECORD_PATH)
+            .ok()
+            .and_then(|contents| contents.trim().parse().ok())
+            .unwrap_or_default();
+        Self {
+            best,
+            set_this_run: false,
+        }
+    }
+
+    pub fn best(&self) -> f32 {
+        self.best
+    }
+
+    pub fn set_this_run(&self) -> bool {
+        self.set_this_run
+    }
+}
+
+/// Records the survival time of every run that ends, with `GameConfig::survival_record`.
+pub struct SurvivalPlugin;
+
+impl Plugin for SurvivalPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SurvivalRecord::load())
+            .add_system(
+                record
+                    .before(show_game_over)
+                    .in_schedule(OnEnter(GameState::GameOver)),
+            )
+            .add_system(record.in_schedule(OnEnter(GameState::Victory)));
+    }
+}
+
+pub fn record(
+    config: Res<GameConfig>,
+    run_time: Res<RunTime>,
+    autopilot: Res<AutopilotControl>,
+    comeback: Res<Comeback>,
+    pipe_rng: Res<PipeRng>,
+    daily: Option<Res<DailyChallenge>>,
+    mut survival: ResMut<SurvivalRecord>,
+) {
+    survival.set_this_run = false;
+    if !config.survival_record
+        || !counts_for_records(&config, &autopilot, &comeback, &pipe_rng, daily.as_deref())
+        // Without pipes there is nothing to survive
+        || !config.spawn_pipes
+        // Daily runs only keep a best score
+        || daily.is_some()
+    {
+        return;
+    }
+    if run_time.0 <= survival.best {
+        return;
+    }
+    survival.best = run_time.0;
+    survival.set_this_run = true;
+    if let Err(err) = save::write_atomic(SURVIVAL_RECORD_PATH, survival.best.to_string()) {
+        error!(
+            "Failed to save survival record to {}: {}",
+            SURVIVAL_RECORD_PATH, err
+        );
+    }
+}
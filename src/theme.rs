@@ -0,0 +1,119 @@
+use std::collections::BTreeMap;
+
+use bevy::{
+    asset::FileAssetIo,
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
+
+use crate::GameConfig;
+
+const PLACEHOLDER_SIZE: u32 = 64;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Fallback {
+    Default,
+    Placeholder,
+}
+
+/// Resolves sprite paths against `GameConfig::theme`. A file the theme doesn't have falls back
+/// to the default sprite, and a missing default to a magenta placeholder, so a partial theme
+/// never leaves anything invisible.
+#[derive(Resource)]
+pub struct Theme {
+    dir: Option<String>,
+    placeholder: Handle<Image>,
+    // Every path that couldn't be loaded as asked, and what was used instead
+    fallbacks: BTreeMap<String, Fallback>,
+}
+
+impl Theme {
+    pub fn image(&mut self, asset_server: &AssetServer, path: &str) -> Handle<Image> {
+        if let Some(dir) = &self.dir {
+            let themed = format!("{}/{}", dir, path);
+            if exists(&themed) {
+                return asset_server.load(themed);
+            }
+        }
+        let fallback = if exists(path) {
+            Fallback::Default
+        } else {
+            Fallback::Placeholder
+        };
+        if self.dir.is_some() || fallback == Fallback::Placeholder {
+            self.fallbacks.insert(path.to_string(), fallback);
+        }
+        match fallback {
+            Fallback::Default => asset_server.load(path),
+            Fallback::Placeholder => self.placeholder.clone(),
+        }
+    }
+
+    /// Like `image`, for decorations the default sprites don't include. None when neither the
+    /// theme nor the defaults have one, instead of a placeholder.
+    pub fn optional_image(&self, asset_server: &AssetServer, path: &str) -> Option<Handle<Image>> {
+        let themed = self.dir.as_ref().map(|dir| format!("{}/{}", dir, path));
+        themed
+            .into_iter()
+            .chain(Some(path.to_string()))
+            .find(|path| exists(path))
+            .map(|path| asset_server.load(path))
+    }
+}
+
+fn exists(path: &str) -> bool {
+    FileAssetIo::get_base_path()
+        .join("assets")
+        .join(path)
+        .exists()
+}
+
+pub struct ThemePlugin;
+
+impl Plugin for ThemePlugin {
+    fn build(&self, app: &mut App) {
+        let dir = app.world.resource::<GameConfig>().theme.clone();
+        let placeholder = app
+            .world
+            .resource_mut::<Assets<Image>>()
+            .add(Image::new_fill(
+                Extent3d {
+                    width: PLACEHOLDER_SIZE,
+                    height: PLACEHOLDER_SIZE,
+                    depth_or_array_layers: 1,
+                },
+                TextureDimension::D2,
+                &[255, 0, 255, 255],
+                TextureFormat::Rgba8UnormSrgb,
+            ));
+        app.insert_resource(Theme {
+            dir,
+            placeholder,
+            fallbacks: BTreeMap::new(),
+        })
+        .add_system(log_fallbacks);
+    }
+}
+
+// Sprites are loaded at startup and at the start of each run, so a summary after either covers
+// everything. Only new fallbacks make it print again
+fn log_fallbacks(theme: Res<Theme>, mut logged: Local<usize>) {
+    if theme.fallbacks.len() == *logged {
+        return;
+    }
+    *logged = theme.fallbacks.len();
+    let summary: Vec<String> = theme
+        .fallbacks
+        .iter()
+        .map(|(path, fallback)| match fallback {
+            Fallback::Default => format!("{} (default)", path),
+            Fallback::Placeholder => format!("{} (placeholder)", path),
+        })
+        .collect();
+    warn!(
+        "Theme {:?} is missing {} sprites: {}",
+        theme.dir.as_deref().unwrap_or("default"),
+        summary.len(),
+        summary.join(", ")
+    );
+}
@@ -0,0 +1,93 @@
+use bevy::prelude::*;
+
+use crate::{update_slow_motion, GameState, HudRoot, TimeScale};
+
+const MIN_MULTIPLIER: f32 = 0.125;
+const MAX_MULTIPLIER: f32 = 8.0;
+
+#[derive(Resource)]
+struct TimeMultiplier(f32);
+
+impl Default for TimeMultiplier {
+    fn default() -> Self {
+        Self(1.0)
+    }
+}
+
+#[derive(Component)]
+struct TimeMultiplierLabel;
+
+/// `]` doubles and `[` halves how fast the simulation runs, to watch long runs play out quickly.
+pub struct TimeControlPlugin;
+
+impl Plugin for TimeControlPlugin {
+    fn build(&self, app: &mut App) {
+        // After the main startup, so the HUD root exists
+        app.init_resource::<TimeMultiplier>()
+            .add_startup_system(spawn_label.in_base_set(StartupSet::PostStartup))
+            .add_system(change_multiplier)
+            .add_system(
+                apply_multiplier
+                    .after(update_slow_motion)
+                    .in_base_set(CoreSet::PreUpdate)
+                    .run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+fn spawn_label(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    hud: Query<Entity, With<HudRoot>>,
+) {
+    commands
+        .spawn((
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 30.0,
+                    color: Color::DARK_GRAY,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.),
+                    bottom: Val::Px(10.),
+                    ..default()
+                },
+                ..default()
+            }),
+            TimeMultiplierLabel,
+        ))
+        .set_parent(hud.single());
+}
+
+fn change_multiplier(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut multiplier: ResMut<TimeMultiplier>,
+    mut label: Query<&mut Text, With<TimeMultiplierLabel>>,
+) {
+    let factor = if keyboard_input.just_pressed(KeyCode::RBracket) {
+        2.0
+    } else if keyboard_input.just_pressed(KeyCode::LBracket) {
+        0.5
+    } else {
+        return;
+    };
+    multiplier.0 = (multiplier.0 * factor).clamp(MIN_MULTIPLIER, MAX_MULTIPLIER);
+    if let Ok(mut text) = label.get_single_mut() {
+        // Nothing to show at normal speed
+        text.sections[0].value = if multiplier.0 == 1.0 {
+            String::new()
+        } else {
+            format!("Time x{}", multiplier.0)
+        };
+    }
+}
+
+// On top of whatever assist mode and slow-motion set this frame
+fn apply_multiplier(multiplier: Res<TimeMultiplier>, mut time_scale: ResMut<TimeScale>) {
+    time_scale.0 *= multiplier.0;
+}
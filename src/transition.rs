@@ -0,0 +1,93 @@
+use bevy::prelude::*;
+
+use crate::{GameConfig, GameState};
+
+// How far the overlay darkens the frozen run behind the end screens
+const END_SCREEN_DIM: f32 = 0.4;
+
+// The overlay fading from one darkness to another since the last state change
+#[derive(Resource, Default)]
+struct Transition {
+    from: f32,
+    to: f32,
+    timer: Timer,
+}
+
+#[derive(Component)]
+struct TransitionOverlay;
+
+/// Eases a full-screen overlay between the darkness of each state instead of cutting, over
+/// `GameConfig::transition_duration`. Reduce motion keeps the cuts.
+pub struct TransitionPlugin;
+
+impl Plugin for TransitionPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Transition>()
+            .add_startup_system(spawn_overlay)
+            .add_system(start_transition)
+            .add_system(fade.after(start_transition));
+    }
+}
+
+fn darkness(state: &GameState) -> f32 {
+    match state {
+        GameState::Playing => 0.0,
+        GameState::GameOver | GameState::Victory => END_SCREEN_DIM,
+    }
+}
+
+fn spawn_overlay(mut commands: Commands) {
+    commands.spawn((
+        NodeBundle {
+            style: Style {
+                size: Size::new(Val::Percent(100.), Val::Percent(100.)),
+                position_type: PositionType::Absolute,
+                ..default()
+            },
+            background_color: Color::rgba(0.0, 0.0, 0.0, 0.0).into(),
+            // Over the world but under the HUD and the end screens
+            z_index: ZIndex::Global(-1),
+            ..default()
+        },
+        TransitionOverlay,
+    ));
+}
+
+fn start_transition(
+    config: Res<GameConfig>,
+    state: Res<State<GameState>>,
+    mut transition: ResMut<Transition>,
+    overlay: Query<&BackgroundColor, With<TransitionOverlay>>,
+) {
+    if !state.is_changed() {
+        return;
+    }
+    let duration = if config.reduce_motion {
+        0.0
+    } else {
+        config.transition_duration
+    };
+    // From wherever the overlay is, in case the previous fade hasn't finished
+    *transition = Transition {
+        from: overlay.get_single().map_or(0.0, |color| color.0.a()),
+        to: darkness(&state.0),
+        timer: Timer::from_seconds(duration, TimerMode::Once),
+    };
+}
+
+// In real time, so slow motion doesn't drag the fade out
+fn fade(
+    time: Res<Time>,
+    mut transition: ResMut<Transition>,
+    mut overlay: Query<&mut BackgroundColor, With<TransitionOverlay>>,
+) {
+    // A fresh timer isn't finished yet, even with no duration, so every fade gets its last step
+    if transition.timer.finished() {
+        return;
+    }
+    transition.timer.tick(time.delta());
+    let alpha = transition.from + (transition.to - transition.from) * transition.timer.percent();
+    if let Ok(mut color) = overlay.get_single_mut() {
+        color.0.set_a(alpha);
+    }
+}
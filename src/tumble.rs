@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{floor_y, GameConfig, GameState, InstantReplay, Movable, Player};
+
+// Slowest spin, as a share of `GameConfig::death_tumble_spin`
+const MIN_SPIN: f32 = 0.5;
+
+// Radians per second the dead bird spins at until it lands
+#[derive(Component)]
+struct Tumble {
+    angular_velocity: f32,
+}
+
+/// Knocks the dead bird into a spin and drops it to the ground behind the game-over screen,
+/// when `GameConfig::death_tumble` is on. Reduce motion keeps it frozen where it died.
+pub struct TumblePlugin;
+
+impl Plugin for TumblePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_system(start.in_schedule(OnEnter(GameState::GameOver)))
+            // Waits for the instant replay, which leaves the bird where it died
+            .add_system(
+                fall.in_set(OnUpdate(GameState::GameOver))
+                    .run_if(|replay: Res<InstantReplay>| !replay.playing()),
+            );
+    }
+}
+
+fn start(mut commands: Commands, config: Res<GameConfig>, player: Query<Entity, With<Player>>) {
+    if !config.death_tumble || config.reduce_motion {
+        return;
+    }
+    let Ok(player) = player.get_single() else {
+        return;
+    };
+    // Purely cosmetic, so this stays off the seeded pipe RNG
+    let mut rng = rand::thread_rng();
+    let direction = if rng.gen_bool(0.5) { 1.0 } else { -1.0 };
+    let spin = rng.gen_range(MIN_SPIN..=1.0) * config.death_tumble_spin;
+    commands.entity(player).insert(Tumble {
+        angular_velocity: direction * spin,
+    });
+}
+
+// Falls with plain gravity whatever flipped it during the run, and settles on the ground
+fn fall(
+    mut commands: Commands,
+    time: Res<Time>,
+    config: Res<GameConfig>,
+    images: Res<Assets<Image>>,
+    mut player: Query<(
+        Entity,
+        &mut Transform,
+        &mut Movable,
+        &Handle<Image>,
+        &Tumble,
+    )>,
+) {
+    let Ok((entity, mut transform, mut movable, sprite, tumble)) = player.get_single_mut() else {
+        return;
+    };
+    let dt = time.delta_seconds();
+    movable.velocity.y += config.gravity * dt;
+    transform.translation.y += movable.velocity.y * dt;
+    transform.rotate_z(tumble.angular_velocity * dt);
+
+    let half_height = images.get(sprite).map_or(0.0, |image| image.size().y / 2.0);
+    let ground = floor_y(&config) + half_height;
+    if transform.translation.y <= ground {
+        transform.translation.y = ground;
+        movable.velocity = Vec3::ZERO;
+        commands.entity(entity).remove::<Tumble>();
+    }
+}
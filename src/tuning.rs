@@ -0,0 +1,191 @@
+use bevy::prelude::*;
+
+use crate::{GameConfig, HudRoot, Movable, Pipe};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+enum Tunable {
+    Gravity,
+    FlapSpeed,
+    PipeSpacing,
+    GapSize,
+}
+
+impl Tunable {
+    const ALL: [Tunable; 4] = [
+        Tunable::Gravity,
+        Tunable::FlapSpeed,
+        Tunable::PipeSpacing,
+        Tunable::GapSize,
+    ];
+
+    fn name(self) -> &'static str {
+        match self {
+            Tunable::Gravity => "Gravity",
+            Tunable::FlapSpeed => "Flap speed",
+            Tunable::PipeSpacing => "Pipe spacing",
+            Tunable::GapSize => "Gap size",
+        }
+    }
+
+    // How much one press of +/- changes the value
+    fn step(self) -> f32 {
+        match self {
+            Tunable::Gravity => 100.0,
+            Tunable::FlapSpeed | Tunable::PipeSpacing => 25.0,
+            Tunable::GapSize => 10.0,
+        }
+    }
+
+    fn value(self, config: &GameConfig) -> f32 {
+        match self {
+            Tunable::Gravity => config.gravity,
+            Tunable::FlapSpeed => config.flap_speed,
+            Tunable::PipeSpacing => config.pipe_spacing_min,
+            Tunable::GapSize => config.gap_size,
+        }
+    }
+
+    fn adjust(self, config: &mut GameConfig, delta: f32) {
+        match self {
+            // A step up means stronger gravity, which is more negative
+            Tunable::Gravity => config.gravity = (config.gravity - delta).min(0.0),
+            Tunable::FlapSpeed => config.flap_speed = (config.flap_speed + delta).max(0.0),
+            // The whole jitter range moves together
+            Tunable::PipeSpacing => {
+                config.pipe_spacing_min += delta;
+                config.pipe_spacing_max += delta;
+            }
+            Tunable::GapSize => config.gap_size = (config.gap_size + delta).max(0.0),
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+struct Selected(usize);
+
+#[derive(Component)]
+struct TuningLabel;
+
+/// Live feel-tuning of the core physics and layout values. 1-4 pick a value, = and - change
+/// it, F7 logs them all. Pipe values apply to pipes as they get recycled.
+pub struct TuningPlugin;
+
+impl Plugin for TuningPlugin {
+    fn build(&self, app: &mut App) {
+        // After the main startup, so the HUD root exists
+        app.init_resource::<Selected>()
+            .add_startup_system(spawn_label.in_base_set(StartupSet::PostStartup))
+            .add_system(tune)
+            .add_system(log_values)
+            .add_system(
+                apply_gravity
+                    .after(tune)
+                    .run_if(resource_changed::<GameConfig>()),
+            )
+            .add_system(update_label.after(tune));
+    }
+}
+
+fn spawn_label(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    hud: Query<Entity, With<HudRoot>>,
+) {
+    commands
+        .spawn((
+            TextBundle::from_section(
+                "",
+                TextStyle {
+                    font: asset_server.load("fonts/FiraSans-Bold.ttf"),
+                    font_size: 24.0,
+                    color: Color::DARK_GRAY,
+                },
+            )
+            .with_style(Style {
+                position_type: PositionType::Absolute,
+                position: UiRect {
+                    left: Val::Px(10.),
+                    top: Val::Px(10.),
+                    ..default()
+                },
+                ..default()
+            }),
+            TuningLabel,
+        ))
+        .set_parent(hud.single());
+}
+
+fn tune(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut selected: ResMut<Selected>,
+    mut config: ResMut<GameConfig>,
+) {
+    let keys = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4];
+    if let Some(index) = keys
+        .iter()
+        .position(|key| keyboard_input.just_pressed(*key))
+    {
+        selected.0 = index;
+    }
+    let tunable = Tunable::ALL[selected.0];
+    if keyboard_input.just_pressed(KeyCode::Equals) {
+        tunable.adjust(&mut config, tunable.step());
+    } else if keyboard_input.just_pressed(KeyCode::Minus) {
+        tunable.adjust(&mut config, -tunable.step());
+    }
+}
+
+// Ready to paste into the config defaults once the feel is right
+fn log_values(keyboard_input: Res<Input<KeyCode>>, config: Res<GameConfig>) {
+    if !keyboard_input.just_pressed(KeyCode::F7) {
+        return;
+    }
+    for tunable in Tunable::ALL {
+        info!("{}: {}", tunable.name(), tunable.value(&config));
+    }
+}
+
+// Birds keep the gravity they spawned with, so push the new value into them. Hovering has none.
+// The config changes for much else besides, so only an actual new gravity is pushed
+fn apply_gravity(
+    config: Res<GameConfig>,
+    mut applied: Local<Option<f32>>,
+    mut birds: Query<&mut Movable, Without<Pipe>>,
+) {
+    if *applied == Some(config.gravity) {
+        return;
+    }
+    *applied = Some(config.gravity);
+    for mut movable in &mut birds {
+        if movable.acceleration.y != 0.0 {
+            movable.acceleration.y = config.gravity;
+        }
+    }
+}
+
+fn update_label(
+    selected: Res<Selected>,
+    config: Res<GameConfig>,
+    mut label: Query<&mut Text, With<TuningLabel>>,
+) {
+    if !selected.is_changed() && !config.is_changed() {
+        return;
+    }
+    let Ok(mut text) = label.get_single_mut() else {
+        return;
+    };
+    text.sections[0].value = Tunable::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, tunable)| {
+            let marker = if i == selected.0 { ">" } else { " " };
+            format!(
+                "{} {} {}: {}\n",
+                marker,
+                i + 1,
+                tunable.name(),
+                tunable.value(&config)
+            )
+        })
+        .collect();
+}